@@ -1,17 +1,17 @@
-use macroquad::{color::Color, math::{vec2, Vec2}};
+use macroquad::{color::Color, math::{vec2, Rect, Vec2}};
 
-use crate::{game::{entity::EntityKind, level::{things::DoorKind, tile::{Tile, TileRenderLayer}, Level, TileRenderData}}, resources::Resources, ui::toast::ToastManager, VIEW_HEIGHT, VIEW_WIDTH};
+use crate::{game::{entity::{launcher::LauncherKind, props::EntityProps, EntityKind}, level::{things::DoorKind, tile::{Tile, TileDir, TileRenderLayer}, Level, TileRenderData}}, resources::Resources, ui::toast::ToastManager, VIEW_HEIGHT, VIEW_WIDTH};
 
-use super::{level_view::editor_camera::EditorCamera};
+use super::level_view::{editor_camera::EditorCamera, layer_panel::EditorLayer};
 
-const MIN_WIDTH:  usize = VIEW_WIDTH;
-const MIN_HEIGHT: usize = VIEW_HEIGHT;
+pub const MIN_WIDTH:  usize = VIEW_WIDTH;
+pub const MIN_HEIGHT: usize = VIEW_HEIGHT;
 // 255 so they fit in a single byte
 // yes it'd be more efficient to store width/height as u8s...
 // but then they're used so much for indexing it'd be annoying to put 'as usize' after everything!!!
 
-const MAX_WIDTH:  usize = 255;
-const MAX_HEIGHT: usize = 255;
+pub const MAX_WIDTH:  usize = 255;
+pub const MAX_HEIGHT: usize = 255;
 
 pub const MAX_SIGNS: usize = 64;
 pub const MAX_DOORS: usize = 255;
@@ -24,6 +24,68 @@ pub const BG_DESERT: (u8, u8, u8) = (235, 204, 162);
 pub const BG_NIGHT: (u8, u8, u8) = (32, 45, 70);
 pub const BG_CLOUD: (u8, u8, u8) = (197, 218, 230);
 
+// Ways of flipping/rotating the level (or part of it)
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum LevelTransform {
+    MirrorH, MirrorV, RotateCw, RotateCcw,
+}
+
+impl LevelTransform {
+    fn dir(self, dir: TileDir) -> TileDir {
+        match self {
+            Self::MirrorH   => dir.flipped_h(),
+            Self::MirrorV   => dir.flipped_v(),
+            Self::RotateCw  => dir.rotated_cw(),
+            Self::RotateCcw => dir.rotated_ccw(),
+        }
+    }
+    fn rotates(self) -> bool {
+        matches!(self, Self::RotateCw | Self::RotateCcw)
+    }
+
+    // Things that face a certain way need to face the other way after being transformed!
+    fn tile(self, tile: Tile) -> Tile {
+        match tile {
+            Tile::Spikes(d) => Tile::Spikes(self.dir(d)),
+            Tile::Cannon(d) => Tile::Cannon(self.dir(d)),
+            // Flame jets are either horizontal or vertical, so they only change when rotating
+            Tile::FlameJet(v) => Tile::FlameJet(v ^ self.rotates()),
+            t => t,
+        }
+    }
+    fn entity(self, kind: EntityKind) -> EntityKind {
+        match kind {
+            EntityKind::Launcher(LauncherKind::Cannonball(d)) => EntityKind::Launcher(LauncherKind::Cannonball(self.dir(d))),
+            EntityKind::FlameJet(v) => EntityKind::FlameJet(v ^ self.rotates()),
+            k => k,
+        }
+    }
+    fn props(self, mut props: EntityProps) -> EntityProps {
+        let (x, y) = (props.vel_x, props.vel_y);
+        (props.vel_x, props.vel_y) = match self {
+            Self::MirrorH   => (-x, y),
+            Self::MirrorV   => (x, -y),
+            Self::RotateCw  => (-y, x),
+            Self::RotateCcw => (y, -x),
+        };
+        // Facing left/right only makes sense to flip when mirroring horizontally
+        if self == Self::MirrorH {
+            props.flip_facing();
+        }
+        props
+    }
+}
+
+// Everything that would be deleted by resizing the level
+#[derive(Default, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct ResizeClip {
+    pub tiles: usize,
+    pub signs: usize,
+    pub doors: usize,
+    pub checkpoints: usize,
+    pub entities: usize,
+}
+
 pub struct EditorLevel {
     world: String,
     name: String,
@@ -42,13 +104,16 @@ pub struct EditorLevel {
     spawn:  Vec2,
     finish: Vec2,
     checkpoints: Vec<Vec2>,
-    entities: Vec<(Vec2, EntityKind)>,
+    entities: Vec<(Vec2, EntityKind, EntityProps)>,
 
     // Rendering stuff
     tiles_below: Vec<TileRenderData>,
     tiles_above: Vec<TileRenderData>,
     tiles_background: Vec<TileRenderData>,
     should_update_render_data: bool,
+
+    // If the level has been edited since the pack was last saved
+    modified: bool,
 }
 
 impl Default for EditorLevel {
@@ -86,6 +151,7 @@ impl Default for EditorLevel {
             tiles_below:      vec![],
             tiles_background: vec![],
             should_update_render_data: true,
+            modified: false,
         }
     }
 }
@@ -104,7 +170,7 @@ impl EditorLevel {
         spawn: Vec2,
         finish: Vec2,
         checkpoints: Vec<Vec2>,
-        entities: Vec<(Vec2, EntityKind)>,
+        entities: Vec<(Vec2, EntityKind, EntityProps)>,
     ) -> Self {
         Self {
             world,
@@ -114,6 +180,7 @@ impl EditorLevel {
             tiles_below:      vec![],
             tiles_background: vec![],
             should_update_render_data: true,
+            modified: false,
         }
     }
 
@@ -140,9 +207,20 @@ impl EditorLevel {
         (&mut self.bg_col.0, &mut self.bg_col.1, &mut self.bg_col.2)
     }
     pub fn set_bg_col(&mut self, bg_col: (u8, u8, u8)) {
+        self.modified |= self.bg_col != bg_col;
         self.bg_col = bg_col;
     }
 
+    pub fn modified(&self) -> bool {
+        self.modified
+    }
+    pub fn mark_modified(&mut self) {
+        self.modified = true;
+    }
+    pub fn clear_modified(&mut self) {
+        self.modified = false;
+    }
+
     pub fn tiles(&self) -> &Vec<Tile> {
         &self.tiles
     }
@@ -173,9 +251,12 @@ impl EditorLevel {
         } else {
             self.signs.push((pos, lines));
         }
+        self.modified = true;
     }
     pub fn try_remove_sign(&mut self, pos: Vec2) {
+        let len = self.signs.len();
         self.signs.retain(|s| s.0 != pos);
+        self.modified |= self.signs.len() != len;
     }
 
     pub fn door_start(&self) -> Option<Vec2> {
@@ -194,18 +275,22 @@ impl EditorLevel {
     pub fn try_add_door(&mut self, kind: DoorKind, pos: Vec2, dest: Vec2, toast_manager: &mut ToastManager) {
         if self.can_add_door() {
             self.doors.push((kind, pos, dest));
+            self.modified = true;
         } else {
             toast_manager.add_door_limit_toast();
         }
     }
     pub fn try_remove_door(&mut self, pos: Vec2) {
+        let len = self.doors.len();
         self.doors.retain(|d| d.1 != pos);
+        self.modified |= self.doors.len() != len;
     }
 
     pub fn spawn(&self) -> Vec2 {
         self.spawn
     }
     pub fn set_spawn(&mut self, spawn: Vec2) {
+        self.modified |= self.spawn != spawn;
         self.spawn = spawn;
     }
 
@@ -213,6 +298,7 @@ impl EditorLevel {
         self.finish
     }
     pub fn set_finish(&mut self, finish: Vec2) {
+        self.modified |= self.finish != finish;
         self.finish = finish;
     }
 
@@ -225,31 +311,57 @@ impl EditorLevel {
         }
         if self.checkpoints.len() < MAX_CHECKPOINTS {
             self.checkpoints.push(pos);
+            self.modified = true;
         } else {
             toast_manager.add_checkpoint_limit_toast();
         }
     }
     pub fn try_remove_checkpoint(&mut self, pos: Vec2) {
+        let len = self.checkpoints.len();
         self.checkpoints.retain(|c| *c != pos);
+        self.modified |= self.checkpoints.len() != len;
     }
 
-    pub fn entities(&self) -> &Vec<(Vec2, EntityKind)> {
+    pub fn entities(&self) -> &Vec<(Vec2, EntityKind, EntityProps)> {
         &self.entities
     }
     pub fn try_add_entity(&mut self, pos: Vec2, kind: EntityKind, toast_manager: &mut ToastManager) {
         // If an entity doesn't exist at this position, add it
-        if self.entities.iter().any(|(p, _)| *p == pos) {
+        if self.entities.iter().any(|(p, ..)| *p == pos) {
             return;
         }
         if self.entities.len() < MAX_ENTITIES {
-            self.entities.push((pos, kind));
+            self.entities.push((pos, kind, EntityProps::default()));
+            self.modified = true;
         } else {
             toast_manager.add_entitiy_limit_toast();
         }
     }
+    pub fn entity_at_pos(&self, pos: Vec2) -> Option<(EntityKind, EntityProps)> {
+        self.entities.iter().find(|(p, ..)| *p == pos).map(|(_, kind, props)| (*kind, *props))
+    }
+    pub fn set_entity_props(&mut self, pos: Vec2, props: EntityProps) {
+        let Some(e) = self.entities.iter_mut().find(|(p, ..)| *p == pos) else {
+            return;
+        };
+        self.modified |= e.2 != props;
+        e.2 = props;
+        // Everything in the same timing group shares the period of the one that was just changed
+        // (different entities have different default periods, so it has to actually be set)
+        if props.group == 0 {
+            return;
+        }
+        let period = props.period_or_default(e.1);
+        for (_, _, other) in self.entities.iter_mut().filter(|(_, _, p)| p.group == props.group) {
+            self.modified |= other.period != period;
+            other.period = period;
+        }
+    }
     pub fn try_remove_entity(&mut self, pos: Vec2) {
         // Remove all entities at this position
-        self.entities.retain(|(p, _)| *p != pos);
+        let len = self.entities.len();
+        self.entities.retain(|(p, ..)| *p != pos);
+        self.modified |= self.entities.len() != len;
     }
 
     // This doesn't check if pos is valid and could crash if it's not,
@@ -263,6 +375,7 @@ impl EditorLevel {
         if tiles[index] != tile {
             tiles[index] = tile;
             self.should_update_render_data = true;
+            self.modified = true;
         }
     }
     pub fn get_tile_at_pos(&self, pos: Vec2, bg: bool) -> Option<Tile> {
@@ -277,6 +390,35 @@ impl EditorLevel {
         }
     }
 
+    // Replaces every 'find' tile with 'replace' on the chosen layers, returning how many were replaced
+    pub fn replace_tiles(&mut self, find: Tile, replace: Tile, fg: bool, bg: bool) -> usize {
+        let mut count = 0;
+        for (layer, tiles) in [(fg, &mut self.tiles), (bg, &mut self.tiles_bg)] {
+            if !layer {
+                continue;
+            }
+            for t in tiles.iter_mut().filter(|t| **t == find) {
+                *t = replace;
+                count += 1;
+            }
+        }
+        if count != 0 {
+            self.should_update_render_data = true;
+            self.modified = true;
+        }
+        count
+    }
+    // Same as above but for entities
+    pub fn replace_entities(&mut self, find: EntityKind, replace: EntityKind) -> usize {
+        let mut count = 0;
+        for (_, kind, _) in self.entities.iter_mut().filter(|(_, k, _)| *k == find) {
+            *kind = replace;
+            count += 1;
+        }
+        self.modified |= count != 0;
+        count
+    }
+
     // These functions are for moving the borders of the level, increasing/decreasing the level's size.
     pub fn can_change_width(&self, increase: bool) -> bool {
             increase && self.width < MAX_WIDTH
@@ -287,6 +429,70 @@ impl EditorLevel {
         || !increase && self.height > MIN_HEIGHT
     }
 
+    pub fn can_resize(width: usize, height: usize) -> bool {
+        (MIN_WIDTH..=MAX_WIDTH).contains(&width) && (MIN_HEIGHT..=MAX_HEIGHT).contains(&height)
+    }
+
+    // How far (in tiles) everything moves when resizing the level to 'width'/'height'.
+    // The anchor is which part of the level stays put, (0, 0) is the top left, (1, 1) the middle and (2, 2) the bottom right.
+    fn resize_offset(&self, width: usize, height: usize, anchor: (usize, usize)) -> (isize, isize) {
+        (
+            (width  as isize - self.width  as isize) * anchor.0 as isize / 2,
+            (height as isize - self.height as isize) * anchor.1 as isize / 2,
+        )
+    }
+
+    // Works out what would be deleted by resizing, so the user can be warned before doing it
+    pub fn resize_clip(&self, width: usize, height: usize, anchor: (usize, usize)) -> ResizeClip {
+        let (dx, dy) = self.resize_offset(width, height, anchor);
+        let outside = |x: isize, y: isize| x < 0 || y < 0 || x >= width as isize || y >= height as isize;
+        let pos_outside = |p: Vec2| outside((p.x / 16.0) as isize + dx, (p.y / 16.0) as isize + dy);
+
+        let mut clip = ResizeClip::default();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if !outside(x as isize + dx, y as isize + dy) {
+                    continue;
+                }
+                let i = y * self.width + x;
+                clip.tiles += (self.tiles[i] != Tile::Empty) as usize + (self.tiles_bg[i] != Tile::Empty) as usize;
+            }
+        }
+        clip.signs = self.signs.iter().filter(|s| pos_outside(s.0)).count();
+        clip.doors = self.doors.iter().filter(|d| pos_outside(d.1) || pos_outside(d.2)).count();
+        clip.checkpoints = self.checkpoints.iter().filter(|c| pos_outside(**c)).count();
+        clip.entities = self.entities.iter().filter(|e| pos_outside(e.0)).count();
+        clip
+    }
+
+    // Resizes the level in one go, rather than moving each border one tile at a time
+    pub fn resize(&mut self, width: usize, height: usize, anchor: (usize, usize)) {
+        if !EditorLevel::can_resize(width, height) || (width, height) == (self.width, self.height) {
+            return;
+        }
+        let (dx, dy) = self.resize_offset(width, height, anchor);
+
+        for tiles in [&mut self.tiles, &mut self.tiles_bg] {
+            let mut new_tiles = vec![Tile::Empty; width * height];
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let (nx, ny) = (x as isize + dx, y as isize + dy);
+                    if nx >= 0 && ny >= 0 && nx < width as isize && ny < height as isize {
+                        new_tiles[ny as usize * width + nx as usize] = tiles[y * self.width + x];
+                    }
+                }
+            }
+            *tiles = new_tiles;
+        }
+        self.width = width;
+        self.height = height;
+
+        self.translate_all_placables(vec2(dx as f32, dy as f32) * 16.0);
+        self.handle_out_of_bounds_placables();
+        self.should_update_render_data = true;
+        self.modified = true;
+    }
+
     fn translate_all_placables(&mut self, offset: Vec2) {
         // Translate everything
         for s in &mut self.signs {
@@ -299,7 +505,7 @@ impl EditorLevel {
         for c in &mut self.checkpoints {
             *c += offset;
         }
-        for (p, _) in &mut self.entities {
+        for (p, ..) in &mut self.entities {
             *p += offset;
         }
         self.spawn += offset;
@@ -371,6 +577,7 @@ impl EditorLevel {
             self.handle_out_of_bounds_placables();
         }
         self.should_update_render_data = true;
+        self.modified = true;
     }
 
     pub fn move_right_border(&mut self, increase: bool, camera: &mut EditorCamera) {
@@ -401,6 +608,7 @@ impl EditorLevel {
             self.handle_out_of_bounds_placables();
         }
         self.should_update_render_data = true;
+        self.modified = true;
     }
 
     pub fn move_top_border(&mut self, increase: bool) {
@@ -429,6 +637,7 @@ impl EditorLevel {
             self.handle_out_of_bounds_placables();
         }
         self.should_update_render_data = true;
+        self.modified = true;
     }
 
     pub fn move_bot_border(&mut self, increase: bool, camera: &mut EditorCamera) {
@@ -455,24 +664,167 @@ impl EditorLevel {
             self.handle_out_of_bounds_placables();
         }
         self.should_update_render_data = true;
+        self.modified = true;
+    }
+
+    // Mirrors or rotates the tiles and placables in 'area' (in pixels, like all of the positions), or the whole level if it's None.
+    // Rotating a part of the level keeps its top-left corner in place, anything rotated out of the level is lost.
+    // Rotating the whole level swaps its width and height (making it bigger if they'd be too small).
+    // Layers that aren't 'editable' (hidden/locked) are left alone, but since rotating the whole level changes its size
+    // that can't be done unless they're all editable, in which case nothing happens and false is returned.
+    pub fn transform(&mut self, transform: LevelTransform, area: Option<Rect>, editable: impl Fn(EditorLayer) -> bool) -> bool {
+        let all_editable = EditorLayer::ALL.into_iter().all(&editable);
+        if area.is_none() && transform.rotates() && !all_editable {
+            return false;
+        }
+
+        // Work out the area in tiles, making sure it's inside the level
+        let (ax, ay, aw, ah) = match area {
+            None => (0, 0, self.width, self.height),
+            Some(r) => {
+                let x = ((r.x / 16.0).floor().max(0.0) as usize).min(self.width);
+                let y = ((r.y / 16.0).floor().max(0.0) as usize).min(self.height);
+                let w = ((r.right()  / 16.0).ceil().max(0.0) as usize).min(self.width)  - x;
+                let h = ((r.bottom() / 16.0).ceil().max(0.0) as usize).min(self.height) - y;
+                (x, y, w, h)
+            }
+        };
+        if aw == 0 || ah == 0 {
+            return true;
+        }
+
+        // Where a tile in the area ends up after being transformed
+        let map = |x: usize, y: usize| -> (usize, usize) {
+            let (rx, ry) = (x - ax, y - ay);
+            let (nx, ny) = match transform {
+                LevelTransform::MirrorH   => (aw - 1 - rx, ry),
+                LevelTransform::MirrorV   => (rx, ah - 1 - ry),
+                LevelTransform::RotateCw  => (ah - 1 - ry, rx),
+                LevelTransform::RotateCcw => (ry, aw - 1 - rx),
+            };
+            (ax + nx, ay + ny)
+        };
+        let in_area = |x: usize, y: usize, (ax, ay, aw, ah): (usize, usize, usize, usize)| {
+            x >= ax && x < ax + aw && y >= ay && y < ay + ah
+        };
+        // The area covered after transforming
+        let dest_area = match transform.rotates() {
+            false => (ax, ay, aw, ah),
+            true  => (ax, ay, ah, aw),
+        };
+
+        let (new_width, new_height) = match (area, transform.rotates()) {
+            (None, true) => (self.height.max(MIN_WIDTH), self.width.max(MIN_HEIGHT)),
+            _ => (self.width, self.height),
+        };
+
+        // Transform the tiles
+        for bg in [false, true] {
+            let (tiles, layer) = match bg {
+                false => (&mut self.tiles,    EditorLayer::Foreground),
+                true  => (&mut self.tiles_bg, EditorLayer::Background),
+            };
+            if !editable(layer) {
+                continue;
+            }
+            let mut new_tiles = match area {
+                None => vec![Tile::Empty; new_width * new_height],
+                Some(_) => tiles.clone(),
+            };
+            // Clear the area so nothing is left behind when rotating a part of the level
+            if area.is_some() {
+                for y in ay..ay+ah {
+                    new_tiles[y*new_width+ax..y*new_width+ax+aw].fill(Tile::Empty);
+                }
+            }
+            for y in ay..ay+ah {
+                for x in ax..ax+aw {
+                    let (nx, ny) = map(x, y);
+                    if nx < new_width && ny < new_height {
+                        new_tiles[ny * new_width + nx] = transform.tile(tiles[y * self.width + x]);
+                    }
+                }
+            }
+            *tiles = new_tiles;
+        }
+
+        // Anything that was already where the area is rotated to gets replaced, just like the tiles
+        if dest_area != (ax, ay, aw, ah) {
+            let replaced = |p: &Vec2| {
+                let (x, y) = ((p.x / 16.0) as usize, (p.y / 16.0) as usize);
+                in_area(x, y, dest_area) && !in_area(x, y, (ax, ay, aw, ah))
+            };
+            self.signs.retain(|s| !editable(EditorLayer::Signs) || !replaced(&s.0));
+            self.doors.retain(|d| !editable(EditorLayer::Doors) || !replaced(&d.1) && !replaced(&d.2));
+            self.checkpoints.retain(|c| !editable(EditorLayer::Checkpoints) || !replaced(c));
+            self.entities.retain(|e| !editable(EditorLayer::Entities) || !replaced(&e.0));
+        }
+
+        // Move everything that's in the area
+        let map_pos = |p: &mut Vec2| -> bool {
+            let (x, y) = ((p.x / 16.0) as usize, (p.y / 16.0) as usize);
+            if !in_area(x, y, (ax, ay, aw, ah)) {
+                return false;
+            }
+            let (nx, ny) = map(x, y);
+            *p = vec2(nx as f32, ny as f32) * 16.0;
+            true
+        };
+        if editable(EditorLayer::Signs) {
+            for s in &mut self.signs {
+                map_pos(&mut s.0);
+            }
+        }
+        if editable(EditorLayer::Doors) {
+            for d in &mut self.doors {
+                map_pos(&mut d.1);
+                map_pos(&mut d.2);
+            }
+        }
+        if editable(EditorLayer::Checkpoints) {
+            for c in &mut self.checkpoints {
+                map_pos(c);
+            }
+        }
+        if editable(EditorLayer::Entities) {
+            for (p, kind, props) in &mut self.entities {
+                if map_pos(p) {
+                    *kind = transform.entity(*kind);
+                    *props = transform.props(*props);
+                }
+            }
+        }
+        // The spawn and finish aren't on a layer of their own, so they only move when nothing is hidden/locked
+        if all_editable {
+            map_pos(&mut self.spawn);
+            map_pos(&mut self.finish);
+        }
+        self.door_start = None;
+
+        self.width  = new_width;
+        self.height = new_height;
+        self.handle_out_of_bounds_placables();
+        self.should_update_render_data = true;
+        self.modified = true;
+        true
     }
 
-    pub fn draw_bg(&self, camera_pos: Vec2, layer_bg: bool, resources: &Resources) {
+    pub fn draw_bg(&self, camera_pos: Vec2, layer_bg: bool, opacity: f32, resources: &Resources) {
         let render_layer = match layer_bg {
-            true  => TileRenderLayer::Foreground(false),
-            false => TileRenderLayer::Background,
+            true  => TileRenderLayer::Foreground(opacity),
+            false => TileRenderLayer::Background(opacity),
         };
         Level::render_tiles(&self.tiles_background, camera_pos, render_layer, resources);
     }
 
-    pub fn draw_fg(&self, camera_pos: Vec2, transparent: bool, resources: &Resources) {
-        Level::render_tiles(&self.tiles_below, camera_pos, TileRenderLayer::Foreground(transparent), resources);
-        Level::render_tiles(&self.tiles_above, camera_pos, TileRenderLayer::Foreground(transparent), resources);
+    pub fn draw_fg(&self, camera_pos: Vec2, opacity: f32, resources: &Resources) {
+        Level::render_tiles(&self.tiles_below, camera_pos, TileRenderLayer::Foreground(opacity), resources);
+        Level::render_tiles(&self.tiles_above, camera_pos, TileRenderLayer::Foreground(opacity), resources);
     }
 
     pub fn update_if_should(&mut self, resources: &Resources) {
         if self.should_update_render_data {
-            Level::update_tile_render_data(&mut self.tiles_below, &mut self.tiles_above, &mut self.tiles_background, None, &self.tiles, &self.tiles_bg, self.width, self.height, resources);
+            Level::update_tile_render_data(&mut self.tiles_below, &mut self.tiles_above, &mut self.tiles_background, None, &self.tiles, &self.tiles_bg, self.width, self.height, resources.tile_data_manager());
             self.should_update_render_data = false;
         }
     }
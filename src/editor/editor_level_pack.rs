@@ -4,6 +4,12 @@ use super::editor_level::EditorLevel;
 
 pub const MAX_LEVELS: usize = 99;
 
+// Which levels something (like find and replace) should affect
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PackScope {
+    Level, World, Pack,
+}
+
 pub struct EditorLevelPack {
     file_name: String,
     name: String,
@@ -11,6 +17,15 @@ pub struct EditorLevelPack {
 
     levels: Vec<EditorLevel>,
     current: usize,
+    // Where each level is in the pack file on disk, or None if it hasn't been saved yet
+    // The death heatmaps are stored by that index, so they need to be moved about when the pack is saved
+    saved_indices: Vec<Option<usize>>,
+    // The old saved indices, set when the pack is saved so the editor can move the deaths around
+    saved_remap: Option<Vec<Option<usize>>>,
+
+    // If the pack itself has been changed (levels added, moved, etc.) since the last save
+    // Edits to the levels are tracked by each level
+    dirty: bool,
 }
 
 impl Default for EditorLevelPack {
@@ -21,13 +36,17 @@ impl Default for EditorLevelPack {
             author: String::new(),
             levels: vec![EditorLevel::default()],
             current: 0,
+            saved_indices: vec![None],
+            saved_remap: None,
+            dirty: false,
         }
     }
 }
 
 impl EditorLevelPack {
     pub fn new(file_name: String, name: String, author: String, levels: Vec<EditorLevel>) -> Self {
-        Self { file_name, name, author, levels, current: 0 }
+        let saved_indices = (0..levels.len()).map(Some).collect();
+        Self { file_name, name, author, levels, current: 0, saved_indices, saved_remap: None, dirty: false }
     }
 
     pub fn file_name(&self) -> &String {
@@ -56,6 +75,23 @@ impl EditorLevelPack {
     pub fn current(&self) -> usize {
         self.current
     }
+    // Where the current level is in the saved pack file
+    pub fn saved_index(&self) -> Option<usize> {
+        self.saved_indices[self.current]
+    }
+    pub fn saved_indices(&self) -> &Vec<Option<usize>> {
+        &self.saved_indices
+    }
+    // Only used by the recovery file, if they don't match up with the levels then they're all treated as unsaved
+    pub fn set_saved_indices(&mut self, saved_indices: Vec<Option<usize>>) {
+        self.saved_indices = match saved_indices.len() == self.levels.len() {
+            true  => saved_indices,
+            false => vec![None; self.levels.len()],
+        };
+    }
+    pub fn take_saved_remap(&mut self) -> Option<Vec<Option<usize>>> {
+        self.saved_remap.take()
+    }
     pub fn level_count(&self) -> usize {
         self.levels.len()
     }
@@ -67,6 +103,39 @@ impl EditorLevelPack {
         &mut self.levels[self.current]
     }
 
+    // If anything in the pack has changed since it was last saved
+    pub fn dirty(&self) -> bool {
+        self.dirty || self.levels.iter().any(|l| l.modified())
+    }
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+    // Should only be called after the pack's been saved, as the levels are now where they are in the file
+    pub fn mark_clean(&mut self) {
+        let saved_indices = (0..self.levels.len()).map(Some).collect();
+        self.saved_remap = Some(std::mem::replace(&mut self.saved_indices, saved_indices));
+        self.dirty = false;
+        for l in &mut self.levels {
+            l.clear_modified();
+        }
+    }
+
+    // The levels in the scope, relative to the current level
+    // A world starts at a level with a world name and goes until the next one
+    pub fn levels_in_scope_mut(&mut self, scope: PackScope) -> &mut [EditorLevel] {
+        let range = match scope {
+            PackScope::Level => self.current..self.current+1,
+            PackScope::Pack  => 0..self.levels.len(),
+            PackScope::World => {
+                let has_world = |i: &usize| !self.levels[*i].world().is_empty();
+                let start = (0..=self.current).rev().find(has_world).unwrap_or(0);
+                let end = (self.current+1..self.levels.len()).find(has_world).unwrap_or(self.levels.len());
+                start..end
+            }
+        };
+        &mut self.levels[range]
+    }
+
     // Bounds checks for manipulating the pack
     // These are separate functions so the buttons can be disabled
     pub fn can_add(&self) -> bool {
@@ -89,7 +158,9 @@ impl EditorLevelPack {
         if self.can_add() {
             self.current += 1;
             self.levels.insert(self.current, EditorLevel::default());
+            self.saved_indices.insert(self.current, None);
             self.editor_level_mut().update_if_should(resources);
+            self.dirty = true;
         }
     }
 
@@ -109,25 +180,50 @@ impl EditorLevelPack {
     pub fn shift_next(&mut self) {
         if self.can_shift_next() {
             self.levels.swap(self.current, self.current + 1);
+            self.saved_indices.swap(self.current, self.current + 1);
             self.current += 1;
+            self.dirty = true;
         }
     }
     pub fn shift_prev(&mut self) {
         if self.can_shift_prev() {
             self.levels.swap(self.current, self.current - 1);
+            self.saved_indices.swap(self.current, self.current - 1);
             self.current -= 1;
+            self.dirty = true;
+        }
+    }
+
+    // Inserts levels (e.g. from another pack) after the current one, stopping at MAX_LEVELS.
+    // Returns how many were actually added.
+    pub fn import_levels(&mut self, levels: Vec<EditorLevel>, resources: &Resources) -> usize {
+        let mut imported = 0;
+        for level in levels {
+            if !self.can_add() {
+                break;
+            }
+            self.current += 1;
+            self.levels.insert(self.current, level);
+            self.saved_indices.insert(self.current, None);
+            imported += 1;
         }
+        self.dirty |= imported > 0;
+        self.editor_level_mut().update_if_should(resources);
+        imported
     }
 
     pub fn delete_level(&mut self, resources: &Resources) {
+        self.dirty = true;
         if self.levels.len() == 1 {
             self.current = 0;
             self.levels.clear();
             self.levels.push(EditorLevel::default());
+            self.saved_indices = vec![None];
             self.editor_level_mut().update_if_should(resources);
             return;
         }
         self.levels.remove(self.current);
+        self.saved_indices.remove(self.current);
         self.current = self.current.clamp(0, self.levels.len() - 1);
         self.editor_level_mut().update_if_should(resources);
     }
@@ -4,19 +4,22 @@ use std::io::Write;
 
 use macroquad::{color::{Color, BLACK, GRAY, WHITE}, color_u8, input::{clear_input_queue, is_key_pressed, KeyCode}, math::{vec2, Rect, Vec2}, shapes::draw_rectangle};
 
-use crate::{level_pack_data::LevelPackData, menu::Menu, resources::Resources, text_renderer::{render_text, Align, Font}, ui::{button::Button, slider_u8::SliderU8, text_input::{TextInput, TextInputKind, TEXT_INPUT_RECT}, toast::{ToastKind, ToastManager}, Ui}, util::{draw_rect, draw_rect_lines}, GameState, VIEW_SIZE};
+use crate::{game::level::{tile::{render_tile, TileRenderLayer}, TileDrawKind, TileRenderData}, level_pack_data::LevelPackData, menu::Menu, resources::Resources, text_renderer::{render_text, Align, Font}, ui::{button::Button, slider_u8::SliderU8, text_input::{TextInput, TextInputKind, MAX_USER_STRING_LEN, TEXT_INPUT_RECT}, toast::{ToastKind, ToastManager}, Ui}, util::{draw_rect, draw_rect_lines}, GameState, VIEW_SIZE};
 
-use super::{editor_level::{BG_CLOUD, BG_DESERT, BG_NIGHT, BG_SKY, BG_SUNSET}, editor_level_pack::EditorLevelPack, level_view::LevelView};
+use super::{editor_level::{EditorLevel, BG_CLOUD, BG_DESERT, BG_NIGHT, BG_SKY, BG_SUNSET, MAX_HEIGHT, MAX_WIDTH, MIN_HEIGHT, MIN_WIDTH}, editor_level_pack::{EditorLevelPack, PackScope}, level_view::{layer_panel::EditorLayer, object_selector::Object, LevelView}, pixel_map, recovery, tiled};
 
 const PACK_EDIT_POS: Vec2 = vec2(5.0, 30.0);
 const BG_COL_POS: Vec2 = vec2(5.0, 120.0);
 const BG_COL: Color = color_u8!(255, 255, 255, 100);
+// How many levels are listed on each page of the import popup
+const IMPORT_PAGE_LEN: usize = 8;
 
 #[derive(PartialEq, Eq, Clone, Copy)]
 #[repr(u8)]
 pub enum HelpKind {
     Editor,
     LevelPacks,
+    Importing,
     Level,
     Resizing,
     ObjectSelector,
@@ -43,7 +46,13 @@ enum HelpScreen {
 
 #[derive(PartialEq, Eq, Clone, Copy)]
 enum PopupKind {
-    None, Save, DeleteLevel, Exit,
+    None, Save, DeleteLevel, Exit, Import, Recovery, Replace, Resize,
+}
+
+// Which object in the replace popup is being picked with the object selector
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum ReplaceSlot {
+    Find, Replace,
 }
 
 pub struct EditorMenu {
@@ -52,6 +61,8 @@ pub struct EditorMenu {
     // The buttons along the top
     help_button: Button,
     save_button: Button,
+    import_button: Button,
+    replace_button: Button,
     exit_button: Button,
 
     last_saved_file_name: String,
@@ -61,6 +72,8 @@ pub struct EditorMenu {
     pack_level_name_input: TextInput,
     pack_add: Button,
     pack_del: Button,
+    pack_resize: Button,
+    pack_export: Button,
     pack_prev: Button,
     pack_next: Button,
     pack_shift_prev: Button,
@@ -81,6 +94,46 @@ pub struct EditorMenu {
     // Shown on exit popup
     exit_popup_cancel: Button,
     exit_popup_exit: Button,
+    // Shown on import popup
+    import_file_name: String,
+    import_pack: Option<LevelPackData>,
+    import_selected: Vec<bool>,
+    import_page: usize,
+    import_popup_file_name_input: TextInput,
+    import_popup_load: Button,
+    import_popup_png: Button,
+    import_popup_tiled: Button,
+    import_popup_levels: Vec<Button>,
+    import_popup_prev: Button,
+    import_popup_next: Button,
+    import_popup_cancel: Button,
+    import_popup_import: Button,
+    // The autosaved pack from a session that wasn't saved, and the popup for restoring it
+    recovery_pack: Option<EditorLevelPack>,
+    recovery_popup_discard: Button,
+    recovery_popup_restore: Button,
+    // Find and replace, with the objects (and their names) to swap
+    replace_find: Option<(Object, String)>,
+    replace_with: Option<(Object, String)>,
+    replace_picking: Option<ReplaceSlot>,
+    replace_fg: bool,
+    replace_bg: bool,
+    replace_scope: PackScope,
+    replace_popup_find: Button,
+    replace_popup_with: Button,
+    replace_popup_layer: Button,
+    replace_popup_scope: Button,
+    replace_popup_cancel: Button,
+    replace_popup_replace: Button,
+    // Resizing the level by typing in a size
+    resize_width: String,
+    resize_height: String,
+    resize_anchor: (usize, usize),
+    resize_popup_width_input: TextInput,
+    resize_popup_height_input: TextInput,
+    resize_popup_anchors: Vec<((usize, usize), Button)>,
+    resize_popup_cancel: Button,
+    resize_popup_resize: Button,
 
     // The bg color sliders
     slider_r: SliderU8,
@@ -109,6 +162,8 @@ impl EditorMenu {
 
             help_button: Button::new(Rect::new(5.0 + 59.0 * 0.0, 5.0, 54.0, 12.0), Some(String::from("Help!")), None),
             save_button: Button::new(Rect::new(5.0 + 59.0 * 1.0, 5.0, 54.0, 12.0), Some(String::from("Save")), None),
+            import_button: Button::new(Rect::new(5.0 + 59.0 * 2.0, 5.0, 54.0, 12.0), Some(String::from("Import")), Some(String::from("Import levels from another pack"))),
+            replace_button: Button::new(Rect::new(5.0 + 59.0 * 3.0, 5.0, 54.0, 12.0), Some(String::from("Replace")), Some(String::from("Find and replace tiles/entities"))),
             exit_button: Button::new(Rect::new(VIEW_SIZE.x - 5.0 - 54.0, 5.0, 54.0, 12.0), Some(String::from("Exit")), None),
 
             last_saved_file_name,
@@ -117,6 +172,8 @@ impl EditorMenu {
             pack_level_name_input: TextInput::new(PACK_EDIT_POS + vec2(53.0, 26.0), TextInputKind::All),
             pack_add: Button::new(Rect::new(PACK_EDIT_POS.x, PACK_EDIT_POS.y + 42.0, 12.0, 12.0), Some(String::from("+")), Some(String::from("Insert new level"))),
             pack_del: Button::new(Rect::new(PACK_EDIT_POS.x + 100.0, PACK_EDIT_POS.y + 42.0, 53.0, 12.0), Some(String::from("Delete")), Some(String::from("Delete current level"))),
+            pack_resize: Button::new(Rect::new(PACK_EDIT_POS.x + 158.0, PACK_EDIT_POS.y + 42.0, 53.0, 12.0), Some(String::from("Resize")), Some(String::from("Resize current level"))),
+            pack_export: Button::new(Rect::new(PACK_EDIT_POS.x + 216.0, PACK_EDIT_POS.y + 42.0, 53.0, 12.0), Some(String::from("Tiled")), Some(String::from("Export level to Tiled (.tmj/.tmx)"))),
             pack_prev:       Button::new(Rect::new(PACK_EDIT_POS.x + 25.0, PACK_EDIT_POS.y + 42.0, 12.0, 12.0), Some(String::from("🮤")), Some(String::from("Previous level"))),
             pack_next:       Button::new(Rect::new(PACK_EDIT_POS.x + 39.0, PACK_EDIT_POS.y + 42.0, 12.0, 12.0), Some(String::from("🮥")), Some(String::from("Next level"))),
             pack_shift_prev: Button::new(Rect::new(PACK_EDIT_POS.x + 60.0, PACK_EDIT_POS.y + 42.0, 12.0, 12.0), Some(String::from("↞")), Some(String::from("Shift level back"))),
@@ -132,6 +189,50 @@ impl EditorMenu {
             delete_popup_delete: Button::new(Rect::new(VIEW_SIZE.x / 2.0 +  5.0, 120.0, 55.0, 12.0), Some(String::from("Delete")), Some(String::from("No going back!"))),
             exit_popup_cancel: Button::new(Rect::new(VIEW_SIZE.x / 2.0 - 60.0, 120.0, 55.0, 12.0), Some(String::from("Cancel")), None),
             exit_popup_exit:   Button::new(Rect::new(VIEW_SIZE.x / 2.0 +  5.0, 120.0, 55.0, 12.0), Some(String::from("Exit")), Some(String::from("No going back!"))),
+            import_file_name: String::new(),
+            import_pack: None,
+            import_selected: vec![],
+            import_page: 0,
+            import_popup_file_name_input: TextInput::new(vec2((VIEW_SIZE.x - TEXT_INPUT_RECT.w) / 2.0, 43.0), TextInputKind::FileName),
+            import_popup_load:  Button::new(Rect::new(VIEW_SIZE.x / 2.0 - 86.0, 58.0, 55.0, 12.0), Some(String::from("Load")), Some(String::from("Load pack file"))),
+            import_popup_png:   Button::new(Rect::new(VIEW_SIZE.x / 2.0 - 27.0, 58.0, 55.0, 12.0), Some(String::from("From PNG")), Some(String::from("Make a level from a png"))),
+            import_popup_tiled: Button::new(Rect::new(VIEW_SIZE.x / 2.0 + 32.0, 58.0, 55.0, 12.0), Some(String::from("Tiled")), Some(String::from("Import a Tiled map (.tmj/.tmx)"))),
+            import_popup_levels: (0..IMPORT_PAGE_LEN)
+                .map(|i| Button::new(Rect::new((VIEW_SIZE.x - TEXT_INPUT_RECT.w) / 2.0, 84.0 + i as f32 * 11.0, TEXT_INPUT_RECT.w, 10.0), None, None))
+                .collect(),
+            import_popup_prev:   Button::new(Rect::new(VIEW_SIZE.x / 2.0 - 40.0 - 12.0, 174.0, 12.0, 12.0), Some(String::from("🮤")), Some(String::from("Previous page"))),
+            import_popup_next:   Button::new(Rect::new(VIEW_SIZE.x / 2.0 + 40.0,        174.0, 12.0, 12.0), Some(String::from("🮥")), Some(String::from("Next page"))),
+            import_popup_cancel: Button::new(Rect::new(VIEW_SIZE.x / 2.0 - 60.0, 190.0, 55.0, 12.0), Some(String::from("Cancel")), None),
+            import_popup_import: Button::new(Rect::new(VIEW_SIZE.x / 2.0 +  5.0, 190.0, 55.0, 12.0), Some(String::from("Import")), Some(String::from("Insert after current level"))),
+            recovery_pack: None,
+            recovery_popup_discard: Button::new(Rect::new(VIEW_SIZE.x / 2.0 - 60.0, 120.0, 55.0, 12.0), Some(String::from("Discard")), Some(String::from("No going back!"))),
+            resize_width: String::new(),
+            resize_height: String::new(),
+            resize_anchor: (1, 1),
+            resize_popup_width_input:  TextInput::new(vec2((VIEW_SIZE.x - TEXT_INPUT_RECT.w) / 2.0, 60.0), TextInputKind::Number),
+            resize_popup_height_input: TextInput::new(vec2((VIEW_SIZE.x - TEXT_INPUT_RECT.w) / 2.0, 84.0), TextInputKind::Number),
+            resize_popup_anchors: (0..9)
+                .map(|i| {
+                    let anchor = (i % 3, i / 3);
+                    let pos = vec2((VIEW_SIZE.x - TEXT_INPUT_RECT.w) / 2.0 + anchor.0 as f32 * 14.0, 112.0 + anchor.1 as f32 * 14.0);
+                    (anchor, Button::new(Rect::new(pos.x, pos.y, 12.0, 12.0), None, Some(String::from("Keep this part of the level in place"))))
+                })
+                .collect(),
+            resize_popup_cancel: Button::new(Rect::new(VIEW_SIZE.x / 2.0 - 60.0, 176.0, 55.0, 12.0), Some(String::from("Cancel")), None),
+            resize_popup_resize: Button::new(Rect::new(VIEW_SIZE.x / 2.0 +  5.0, 176.0, 55.0, 12.0), Some(String::from("Resize")), None),
+            replace_find: None,
+            replace_with: None,
+            replace_picking: None,
+            replace_fg: true,
+            replace_bg: false,
+            replace_scope: PackScope::Level,
+            replace_popup_find: Button::new(Rect::new(VIEW_SIZE.x / 2.0 - 60.0, 75.0, 16.0, 16.0), None, Some(String::from("Pick object to find"))),
+            replace_popup_with: Button::new(Rect::new(VIEW_SIZE.x / 2.0 - 60.0, 95.0, 16.0, 16.0), None, Some(String::from("Pick object to replace it with"))),
+            replace_popup_layer: Button::new(Rect::new(VIEW_SIZE.x / 2.0 - 90.0, 117.0, 85.0, 12.0), Some(String::from("Layer: FG")), Some(String::from("Which tile layers to change"))),
+            replace_popup_scope: Button::new(Rect::new(VIEW_SIZE.x / 2.0 +  5.0, 117.0, 85.0, 12.0), Some(String::from("In: Level")), Some(String::from("Which levels to change"))),
+            replace_popup_cancel:  Button::new(Rect::new(VIEW_SIZE.x / 2.0 - 60.0, 135.0, 55.0, 12.0), Some(String::from("Cancel")), None),
+            replace_popup_replace: Button::new(Rect::new(VIEW_SIZE.x / 2.0 +  5.0, 135.0, 55.0, 12.0), Some(String::from("Replace")), None),
+            recovery_popup_restore: Button::new(Rect::new(VIEW_SIZE.x / 2.0 +  5.0, 120.0, 55.0, 12.0), Some(String::from("Restore")), Some(String::from("Replaces the current pack"))),
 
             slider_r: SliderU8::new(0, 255, Rect::new(BG_COL_POS.x + 33.0, BG_COL_POS.y + 5.0, 256.0, 10.0)),
             slider_g: SliderU8::new(0, 255, Rect::new(BG_COL_POS.x + 33.0, BG_COL_POS.y + 20.0, 256.0, 10.0)),
@@ -151,12 +252,49 @@ impl EditorMenu {
     pub fn set_active(&mut self, active: bool) {
         if active {
             self.help_screen = HelpScreen::Closed;
+            self.replace_picking = None;
         }
         self.pack_level_name_input.deactivate();
         self.active = active;
         clear_input_queue();
     }
 
+    // If the menu is waiting for the object selector to pick something
+    pub fn picking_object(&self) -> bool {
+        self.replace_picking.is_some()
+    }
+    pub fn set_picked_object(&mut self, object: Object, name: String) {
+        // Only tiles and entities can be replaced
+        if matches!(object, Object::Tile(_) | Object::Entity(_)) {
+            match self.replace_picking {
+                Some(ReplaceSlot::Find)    => self.replace_find = Some((object, name)),
+                Some(ReplaceSlot::Replace) => self.replace_with = Some((object, name)),
+                None => {}
+            }
+        }
+        self.replace_picking = None;
+        self.active = true;
+    }
+
+    pub fn open_resize_popup(&mut self, editor_level: &EditorLevel) {
+        self.resize_width  = editor_level.width().to_string();
+        self.resize_height = editor_level.height().to_string();
+        self.popup = PopupKind::Resize;
+    }
+
+    // The size typed into the resize popup, if it's valid
+    fn resize_size(&self) -> Option<(usize, usize)> {
+        let size = (self.resize_width.parse().ok()?, self.resize_height.parse().ok()?);
+        EditorLevel::can_resize(size.0, size.1).then_some(size)
+    }
+
+    pub fn open_recovery_popup(&mut self, recovery_pack: EditorLevelPack) {
+        self.active = true;
+        self.help_screen = HelpScreen::Closed;
+        self.popup = PopupKind::Recovery;
+        self.recovery_pack = Some(recovery_pack);
+    }
+
     pub fn open_help_menu(&mut self, help_kind: HelpKind) {
         self.pack_level_name_input.deactivate();
         self.active = true;
@@ -203,9 +341,13 @@ impl EditorMenu {
         resources: &Resources
     ) {
         if self.popup == PopupKind::Save {
+            let before = (editor_level_pack.file_name().clone(), editor_level_pack.name().clone(), editor_level_pack.author().clone());
             self.pack_popup_file_name_input.update(editor_level_pack.file_name_mut(), deltatime, ui, resources);
             self.pack_popup_name_input.update(editor_level_pack.name_mut(), deltatime, ui, resources);
             self.pack_popup_author_input.update(editor_level_pack.author_mut(), deltatime, ui, resources);
+            if before != (editor_level_pack.file_name().clone(), editor_level_pack.name().clone(), editor_level_pack.author().clone()) {
+                editor_level_pack.mark_dirty();
+            }
             self.pack_popup_cancel.update(ui);
             self.pack_popup_save.update(ui);
 
@@ -214,7 +356,7 @@ impl EditorMenu {
             }
             if self.pack_popup_save.released() {            
                 let pack_data = LevelPackData::from_editor_level_pack(editor_level_pack);
-                let bytes = pack_data.to_bytes(resources);
+                let bytes = pack_data.to_bytes();
                 let file_name = pack_data.file_name().clone();
 
                 if file_name.is_empty() {
@@ -230,19 +372,22 @@ impl EditorMenu {
                 {
                     Ok(f) => f,
                     Err(e) => {
-                        toast_manager.add_toast(String::from("Error creating file?!"), ToastKind::Warning);
-                        toast_manager.add_toast(format!("{e}"), ToastKind::Warning);
+                        toast_manager.add_toast(String::from("Error creating file?!"), ToastKind::Error);
+                        toast_manager.add_toast(format!("{e}"), ToastKind::Error);
                         return;
                     }
                 };
                 // Write the level pack bytes
                 if let Err(e) = file.write_all(&bytes) {
-                    toast_manager.add_toast(String::from("Error writing bytes to file?!"), ToastKind::Warning);
-                    toast_manager.add_toast(format!("{e}"), ToastKind::Warning);
+                    toast_manager.add_toast(String::from("Error writing bytes to file?!"), ToastKind::Error);
+                    toast_manager.add_toast(format!("{e}"), ToastKind::Error);
                     return;
                 }
-                toast_manager.add_toast(format!("Saved pack to {}.fox", file_name), ToastKind::Info);
+                toast_manager.add_toast(format!("Saved pack to {}.fox", file_name), ToastKind::Success);
                 self.last_saved_file_name = file_name;
+                // Everything's safe now, so the autosave isn't needed
+                editor_level_pack.mark_clean();
+                recovery::remove_recovery_file();
                 self.popup = PopupKind::None;
             }
         } else if self.popup == PopupKind::DeleteLevel {
@@ -264,11 +409,262 @@ impl EditorMenu {
                 self.popup = PopupKind::None;
             }
             if self.exit_popup_exit.released() {
-                *next_state = Some(Box::new(Menu::new(Some(self.last_saved_file_name.clone()))));
+                // Keep any unsaved changes in the recovery file, just in case
+                match editor_level_pack.dirty() {
+                    true  => { let _ = recovery::write_recovery_file(editor_level_pack); }
+                    false => recovery::remove_recovery_file(),
+                }
+                *next_state = Some(Box::new(Menu::new(Some(self.last_saved_file_name.clone()), toast_manager)));
+            }
+        } else if self.popup == PopupKind::Recovery {
+            self.recovery_popup_discard.update(ui);
+            self.recovery_popup_restore.update(ui);
+
+            if self.recovery_popup_discard.released() {
+                recovery::remove_recovery_file();
+                self.recovery_pack = None;
+                self.popup = PopupKind::None;
+            }
+            if self.recovery_popup_restore.released() {
+                if let Some(mut pack) = self.recovery_pack.take() {
+                    // It's still not saved!
+                    pack.mark_dirty();
+                    pack.editor_level_mut().update_if_should(resources);
+                    *editor_level_pack = pack;
+                    level_view.reset_camera();
+                    toast_manager.add_toast(String::from("Restored unsaved pack"), ToastKind::Success);
+                }
+                self.popup = PopupKind::None;
+            }
+        } else if self.popup == PopupKind::Resize {
+            self.resize_popup_width_input.update(&mut self.resize_width, deltatime, ui, resources);
+            self.resize_popup_height_input.update(&mut self.resize_height, deltatime, ui, resources);
+            for (anchor, b) in &mut self.resize_popup_anchors {
+                b.update(ui);
+                if b.released() {
+                    self.resize_anchor = *anchor;
+                }
+            }
+            let level = editor_level_pack.editor_level();
+            self.resize_popup_resize.set_disabled(self.resize_size().is_none_or(|s| s == (level.width(), level.height())));
+            self.resize_popup_cancel.update(ui);
+            self.resize_popup_resize.update(ui);
+
+            if self.resize_popup_cancel.released() {
+                self.popup = PopupKind::None;
+            }
+            if let (true, Some((width, height))) = (self.resize_popup_resize.released(), self.resize_size()) {
+                // Resizing can cut off any of the layers
+                if !level_view.layer_panel().all_editable() {
+                    toast_manager.add_toast(String::from("Can't resize with hidden or locked layers!"), ToastKind::Warning);
+                } else {
+                    editor_level_pack.editor_level_mut().resize(width, height, self.resize_anchor);
+                    editor_level_pack.editor_level_mut().update_if_should(resources);
+                    level_view.reset_camera();
+                    self.popup = PopupKind::None;
+                }
+            }
+        } else if self.popup == PopupKind::Replace {
+            self.update_replace_popup(editor_level_pack, level_view, toast_manager, ui, resources);
+        } else if self.popup == PopupKind::Import {
+            self.import_popup_file_name_input.update(&mut self.import_file_name, deltatime, ui, resources);
+            self.import_popup_load.update(ui);
+            self.import_popup_png.update(ui);
+            self.import_popup_tiled.update(ui);
+            self.import_popup_cancel.update(ui);
+
+            if self.import_popup_cancel.released() {
+                self.popup = PopupKind::None;
+            }
+            if self.import_popup_load.released() {
+                self.load_import_pack(toast_manager);
+            }
+            // Making a single level from a png or Tiled map
+            let single_level = match (self.import_popup_png.released(), self.import_popup_tiled.released()) {
+                (true, _) => Some(pixel_map::level_from_png(&self.import_file_name)),
+                (_, true) => Some(tiled::import_level(&self.import_file_name)),
+                _ => None,
+            };
+            if let Some(level) = single_level {
+                match level {
+                    Ok(level) => {
+                        if editor_level_pack.import_levels(vec![level], resources) == 0 {
+                            toast_manager.add_level_limit_toast();
+                        } else {
+                            toast_manager.add_toast(String::from("Imported level"), ToastKind::Success);
+                            level_view.reset_camera();
+                            self.popup = PopupKind::None;
+                        }
+                    }
+                    Err(e) => toast_manager.add_toast(e, ToastKind::Warning),
+                }
+                return;
+            }
+
+            let level_count = match &self.import_pack {
+                Some(p) => p.levels().len(),
+                None => return,
+            };
+
+            // Selecting levels on the current page
+            for (i, b) in self.import_popup_levels.iter_mut().enumerate() {
+                let index = self.import_page * IMPORT_PAGE_LEN + i;
+                b.set_disabled(index >= level_count);
+                b.update(ui);
+                if b.released() {
+                    if let Some(selected) = self.import_selected.get_mut(index) {
+                        *selected = !*selected;
+                    }
+                }
+            }
+
+            // Changing the page
+            self.import_popup_prev.set_disabled(self.import_page == 0);
+            self.import_popup_next.set_disabled((self.import_page + 1) * IMPORT_PAGE_LEN >= level_count);
+            self.import_popup_prev.update(ui);
+            self.import_popup_next.update(ui);
+            if self.import_popup_prev.released() {
+                self.import_page -= 1;
+            }
+            if self.import_popup_next.released() {
+                self.import_page += 1;
+            }
+
+            self.import_popup_import.set_disabled(!self.import_selected.contains(&true));
+            self.import_popup_import.update(ui);
+            if self.import_popup_import.released() {
+                let indices: Vec<usize> = self.import_selected
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, s)| s.then_some(i))
+                    .collect();
+                let levels = match &self.import_pack {
+                    Some(p) => p.to_editor_levels(&indices),
+                    None => return,
+                };
+                let wanted = levels.len();
+                let imported = editor_level_pack.import_levels(levels, resources);
+                level_view.reset_camera();
+
+                if imported < wanted {
+                    toast_manager.add_level_limit_toast();
+                }
+                toast_manager.add_toast(format!("Imported {} level{}", imported, if imported == 1 { "" } else { "s" }), ToastKind::Success);
+                self.popup = PopupKind::None;
             }
         }
     }
 
+    fn update_replace_popup(&mut self, editor_level_pack: &mut EditorLevelPack, level_view: &mut LevelView, toast_manager: &mut ToastManager, ui: &mut Ui, resources: &Resources) {
+        let find_tile = matches!(self.replace_find, Some((Object::Tile(_), _)));
+        let same_kind = matches!(
+            (&self.replace_find, &self.replace_with),
+            (Some((Object::Tile(_), _)),   Some((Object::Tile(_), _))) |
+            (Some((Object::Entity(_), _)), Some((Object::Entity(_), _)))
+        );
+        self.replace_popup_layer.set_disabled(!find_tile);
+        self.replace_popup_replace.set_disabled(!same_kind);
+
+        self.replace_popup_find.update(ui);
+        self.replace_popup_with.update(ui);
+        self.replace_popup_layer.update(ui);
+        self.replace_popup_scope.update(ui);
+        self.replace_popup_cancel.update(ui);
+        self.replace_popup_replace.update(ui);
+
+        // Picking the objects with the object selector
+        for (button, slot) in [(&self.replace_popup_find, ReplaceSlot::Find), (&self.replace_popup_with, ReplaceSlot::Replace)] {
+            if button.released() {
+                self.replace_picking = Some(slot);
+                self.active = false;
+                level_view.open_object_selector();
+            }
+        }
+        // Cycling through the layers/scopes
+        if self.replace_popup_layer.released() {
+            (self.replace_fg, self.replace_bg) = match (self.replace_fg, self.replace_bg) {
+                (true, false) => (false, true),
+                (false, true) => (true, true),
+                _             => (true, false),
+            };
+            self.replace_popup_layer.set_label(match (self.replace_fg, self.replace_bg) {
+                (true, false) => "Layer: FG",
+                (false, true) => "Layer: BG",
+                _             => "Layer: Both",
+            });
+        }
+        if self.replace_popup_scope.released() {
+            self.replace_scope = match self.replace_scope {
+                PackScope::Level => PackScope::World,
+                PackScope::World => PackScope::Pack,
+                PackScope::Pack  => PackScope::Level,
+            };
+            self.replace_popup_scope.set_label(format!("In: {:?}", self.replace_scope));
+        }
+
+        if self.replace_popup_cancel.released() {
+            self.popup = PopupKind::None;
+        }
+        if self.replace_popup_replace.released() {
+            // Hidden/locked layers are left alone
+            let layer_panel = level_view.layer_panel();
+            let fg = self.replace_fg && layer_panel.editable(EditorLayer::Foreground);
+            let bg = self.replace_bg && layer_panel.editable(EditorLayer::Background);
+            let entities = layer_panel.editable(EditorLayer::Entities);
+
+            let levels = editor_level_pack.levels_in_scope_mut(self.replace_scope);
+            let count: usize = match (&self.replace_find, &self.replace_with) {
+                (Some((Object::Tile(_), _)), Some((Object::Tile(_), _))) if !fg && !bg => {
+                    toast_manager.add_toast(String::from("Those layers are hidden or locked!"), ToastKind::Warning);
+                    return;
+                }
+                (Some((Object::Entity(_), _)), Some((Object::Entity(_), _))) if !entities => {
+                    toast_manager.add_toast(String::from("That layer is hidden or locked!"), ToastKind::Warning);
+                    return;
+                }
+                (Some((Object::Tile(find), _)), Some((Object::Tile(with), _))) => levels
+                    .iter_mut()
+                    .map(|l| l.replace_tiles(*find, *with, fg, bg))
+                    .sum(),
+                (Some((Object::Entity(find), _)), Some((Object::Entity(with), _))) => levels
+                    .iter_mut()
+                    .map(|l| l.replace_entities(*find, *with))
+                    .sum(),
+                _ => return,
+            };
+            editor_level_pack.editor_level_mut().update_if_should(resources);
+            toast_manager.add_toast(format!("Replaced {} object{}", count, if count == 1 { "" } else { "s" }), ToastKind::Info);
+            self.popup = PopupKind::None;
+        }
+    }
+
+    // Loads the pack typed into the import popup so its levels can be picked from
+    fn load_import_pack(&mut self, toast_manager: &mut ToastManager) {
+        self.import_pack = None;
+        self.import_selected.clear();
+        self.import_page = 0;
+
+        if self.import_file_name.is_empty() {
+            toast_manager.add_toast("Can't leave file name blank!".to_string(), ToastKind::Warning);
+            return;
+        }
+        let bytes = match std::fs::read(format!("{}.fox", self.import_file_name)) {
+            Ok(b) => b,
+            Err(e) => {
+                toast_manager.add_couldnt_pack_open_file();
+                toast_manager.add_toast(format!("{e}"), ToastKind::Warning);
+                return;
+            }
+        };
+        match LevelPackData::from_bytes(self.import_file_name.clone(), &bytes) {
+            Some(p) => {
+                self.import_selected = vec![false; p.levels().len()];
+                self.import_pack = Some(p);
+            }
+            None => toast_manager.add_invalid_pack_toast(),
+        }
+    }
+
     pub fn update(
         &mut self,
         next_state: &mut Option<Box<dyn GameState>>,
@@ -301,6 +697,10 @@ impl EditorMenu {
             return;
         }
 
+        // Remember the level's names and color so we can tell if they've been edited
+        let level = editor_level_pack.editor_level();
+        let before = (level.world().clone(), level.name().clone(), level.bg_col());
+
         self.pack_level_world_input.update(editor_level_pack.editor_level_mut().world_mut(), deltatime, ui, resources);
         self.pack_level_name_input.update(editor_level_pack.editor_level_mut().name_mut(), deltatime, ui, resources);
         self.pack_add.set_disabled(!editor_level_pack.can_add());
@@ -312,6 +712,8 @@ impl EditorMenu {
         // Update the pack edit ui thingies
         self.pack_add.update(ui);
         self.pack_del.update(ui);
+        self.pack_resize.update(ui);
+        self.pack_export.update(ui);
         self.pack_prev.update(ui);
         self.pack_next.update(ui);
         self.pack_shift_prev.update(ui);
@@ -324,6 +726,21 @@ impl EditorMenu {
         if self.pack_del.released() {
             self.popup = PopupKind::DeleteLevel;
         }
+        if self.pack_resize.released() {
+            self.open_resize_popup(editor_level_pack.editor_level());
+        }
+        if self.pack_export.released() {
+            // Named after the pack and the level number, e.g. 'my_pack_03.tmj' and 'my_pack_03.tmx'
+            let pack_name = match editor_level_pack.file_name().is_empty() {
+                true  => "level",
+                false => editor_level_pack.file_name(),
+            };
+            let file_name = format!("{}_{:0>2}", pack_name, editor_level_pack.current() + 1);
+            match tiled::export_level(editor_level_pack.editor_level(), &file_name) {
+                Ok(_)  => toast_manager.add_toast(format!("Exported to {file_name}.tmj/.tmx"), ToastKind::Success),
+                Err(e) => toast_manager.add_toast(e, ToastKind::Warning),
+            }
+        }
         if self.pack_prev.released() {
             editor_level_pack.prev(resources);
             level_view.reset_camera();
@@ -350,9 +767,16 @@ impl EditorMenu {
         self.slider_g.update(editor_level_pack.editor_level_mut().bg_col_mut().1, ui);
         self.slider_b.update(editor_level_pack.editor_level_mut().bg_col_mut().2, ui);
 
+        let level = editor_level_pack.editor_level();
+        if before != (level.world().clone(), level.name().clone(), level.bg_col()) {
+            editor_level_pack.editor_level_mut().mark_modified();
+        }
+
         // Update other buttons
         self.help_button.update(ui);
         self.save_button.update(ui);
+        self.import_button.update(ui);
+        self.replace_button.update(ui);
         self.exit_button.update(ui);
 
         if self.help_button.released() {
@@ -361,6 +785,12 @@ impl EditorMenu {
         if self.save_button.released() {
             self.popup = PopupKind::Save;
         }
+        if self.import_button.released() {
+            self.popup = PopupKind::Import;
+        }
+        if self.replace_button.released() {
+            self.popup = PopupKind::Replace;
+        }
         if self.exit_button.released() {
             self.popup = PopupKind::Exit;
         }
@@ -374,6 +804,7 @@ impl EditorMenu {
         /*
         Editor,
         LevelPacks,
+        Importing,
         Level,
         Resizing,
         ObjectSelector,
@@ -392,6 +823,9 @@ impl EditorMenu {
                 "correspond to the currently selected",
                 "object!",
                 "",
+                "The 'Test' button picks the powerups,",
+                "lives, locks etc. to playtest with.",
+                "F5/F9 quicksave/load while testing.",
                 "Happy editing! :3"
             ]),
             p if p == HelpKind::LevelPacks as u8 => ("Level packs", &[
@@ -403,10 +837,30 @@ impl EditorMenu {
                 "'ESCAPE') allows you to:",
                 " - Add / remove levels.",
                 " - Reorder levels in the pack.",
+                " - Import levels from other packs.",
                 " - Change the level you're currently",
                 "   editing.",
                 " - Access this help menu.",
                 " - Test the level with 'tab'",
+                "",
+                "Unsaved changes are autosaved, and",
+                "can be restored if the game closes.",
+            ]),
+            p if p == HelpKind::Importing as u8 => ("Importing", &[
+                //-----------------------------------//
+                "'Import' in the menu can add levels",
+                "from another pack, a png ('From PNG')",
+                "or a Tiled .tmj/.tmx map ('Tiled').",
+                "",
+                "Each pixel of the png is a tile, the",
+                "colours are set in 'pixel_map.txt'.",
+                "White is empty, 00FF00 is the spawn,",
+                "FF0000 the finish, 0000FF checkpoints",
+                "and FF00xx is an entity (where xx is",
+                "80 + its id in hex).",
+                "",
+                "'Tiled' next to 'Resize' exports the",
+                "level (tile ids are the .fox ones).",
             ]),
             p if p == HelpKind::Level as u8 => ("Level", &[
                 //-----------------------------------//
@@ -415,7 +869,14 @@ impl EditorMenu {
                 "or use the WASD keys.",
                 "",
                 "The level can be resized (mentioned",
-                "on the next help page)."
+                "on the next help page).",
+                "",
+                "Drag with 'ctrl' held to select an",
+                "area, right click to deselect it.",
+                "'ctrl+H'/'ctrl+V' mirror the area (or",
+                "the whole level) horizontally or",
+                "vertically, 'ctrl+R' rotates it and",
+                "'ctrl+shift+R' rotates it back."
             ]),
             p if p == HelpKind::Resizing as u8 => ("Resizing", &[
                 //-----------------------------------//
@@ -431,6 +892,7 @@ impl EditorMenu {
                 "(tiles/entities/others) that become",
                 "out of bounds will be deleted (with a",
                 "few exceptions mentioned elsewhere).",
+                "The menu has a precise resize too!",
             ]),
             p if p == HelpKind::ObjectSelector as u8 => ("Object selector", &[
                 //-----------------------------------//
@@ -455,7 +917,11 @@ impl EditorMenu {
                 "to erase tiles.",
                 "",
                 "Tiles like the cannon do *NOTHING*",
-                "you gotta place the entity there too!"
+                "you gotta place the entity there too!",
+                "",
+                "Press 'I' to see a tile's collision",
+                "and texture info, and F1 to color",
+                "tiles by their collision.",
             ]),
             p if p == HelpKind::BackgroundTiles as u8 => ("Background tiles", &[
                 //-----------------------------------//
@@ -466,7 +932,11 @@ impl EditorMenu {
                 "",
                 "Switch to the background by pressing",
                 "the FG/BG button when editing.",
-                "You can also use the hotkey 'f'."
+                "You can also use the hotkey 'f'.",
+                "",
+                "The 'Layers' button lets you hide,",
+                "fade ('%') or lock each layer, so you",
+                "don't change them by accident."
             ]),
             p if p == HelpKind::Entities as u8 => ("Entities", &[
                 //-----------------------------------//
@@ -478,6 +948,11 @@ impl EditorMenu {
                 "Some entities are used for spawning",
                 "cannon balls or flame jets, some for",
                 "enemies, and some for pickups!",
+                "",
+                "Left click a placed entity to change",
+                "its props, like which way it faces,",
+                "its speed, or its timing. Timed ones",
+                "in a group all share the same period!",
             ]),
             p if p == HelpKind::Signs as u8 => ("Signs", &[
                 //-----------------------------------//
@@ -524,11 +999,15 @@ impl EditorMenu {
                 "The spawn and finish are the points",
                 "where the player first spawns in,",
                 "and where they complete the level.",
-                "", "", "",
+                "",
                 "They always exist and cannot be",
                 "removed. If the level resizes and they",
                 "are on the edge, they are pushed back",
                 "to being in the bounds of the level.",
+                "",
+                "Press 'J' on a tile to see how far the",
+                "player can jump from it, and 'K' to",
+                "change the feet powerup.",
             ]),
             p if p == HelpKind::Checkpoints as u8 => ("Checkpoints", &[
                 //-----------------------------------//
@@ -538,7 +1017,11 @@ impl EditorMenu {
                 "*take care when placing them!*",
                 "nothing is remembered when a player",
                 "respawns at one, so you could possibly",
-                "softlock them with lock blocks etc..!!"
+                "softlock them with lock blocks etc..!!",
+                "",
+                "Press 'M' to show where the player",
+                "has died in this level (from testing",
+                "and playing), 'shift+M' clears it.",
             ]),
 
             _ => ("help screen", &["page error!! ???"])
@@ -575,9 +1058,137 @@ impl EditorMenu {
             draw_rect(rect, GRAY);
             draw_rect_lines(rect, BLACK);
             render_text("Exit to the main menu?", WHITE, rect.point() + vec2(4.0,  3.0), Vec2::ONE, Align::End, Font::Small, resources);
-            render_text("(Remember to save!!)", WHITE, rect.point() + vec2(4.0, 13.0), Vec2::ONE, Align::End, Font::Small, resources);
+            let reminder = match editor_level_pack.dirty() {
+                true  => "(Unsaved changes!!)",
+                false => "(Remember to save!!)",
+            };
+            render_text(reminder, WHITE, rect.point() + vec2(4.0, 13.0), Vec2::ONE, Align::End, Font::Small, resources);
             self.exit_popup_cancel.draw(resources);
             self.exit_popup_exit.draw(resources);
+        } else if self.popup == PopupKind::Recovery {
+            let rect = Rect::new((VIEW_SIZE.x - TEXT_INPUT_RECT.w) / 2.0 - 4.0, 87.0, TEXT_INPUT_RECT.w + 8.0, 48.0);
+            draw_rect(rect, GRAY);
+            draw_rect_lines(rect, BLACK);
+            render_text("Restore unsaved pack?", WHITE, rect.point() + vec2(4.0,  3.0), Vec2::ONE, Align::End, Font::Small, resources);
+            if let Some(pack) = &self.recovery_pack {
+                let file_name = match pack.file_name().is_empty() {
+                    true  => "(no file name)",
+                    false => pack.file_name(),
+                };
+                render_text(file_name, WHITE, rect.point() + vec2(4.0, 13.0), Vec2::ONE, Align::End, Font::Small, resources);
+                render_text(&format!("{} level{}", pack.level_count(), if pack.level_count() == 1 { "" } else { "s" }), WHITE, rect.point() + vec2(4.0, 23.0), Vec2::ONE, Align::End, Font::Small, resources);
+            }
+            self.recovery_popup_discard.draw(resources);
+            self.recovery_popup_restore.draw(resources);
+        } else if self.popup == PopupKind::Resize {
+            let rect = Rect::new((VIEW_SIZE.x - TEXT_INPUT_RECT.w) / 2.0 - 4.0, 40.0, TEXT_INPUT_RECT.w + 8.0, 152.0);
+            draw_rect(rect, GRAY);
+            draw_rect_lines(rect, BLACK);
+            render_text("Resize level", WHITE, rect.point() + vec2(4.0, 3.0), Vec2::ONE, Align::End, Font::Small, resources);
+            render_text(&format!("Width ({}-{})", MIN_WIDTH, MAX_WIDTH),    WHITE, rect.point() + vec2(4.0, 12.0), Vec2::ONE, Align::End, Font::Small, resources);
+            render_text(&format!("Height ({}-{})", MIN_HEIGHT, MAX_HEIGHT), WHITE, rect.point() + vec2(4.0, 36.0), Vec2::ONE, Align::End, Font::Small, resources);
+            self.resize_popup_width_input.draw(&self.resize_width, "Width", resources);
+            self.resize_popup_height_input.draw(&self.resize_height, "Height", resources);
+
+            render_text("Anchor", WHITE, rect.point() + vec2(4.0, 61.0), Vec2::ONE, Align::End, Font::Small, resources);
+            for (anchor, b) in &self.resize_popup_anchors {
+                b.draw(resources);
+                if *anchor == self.resize_anchor {
+                    render_text("▪", WHITE, b.rect().center(), Vec2::ONE, Align::Mid, Font::Small, resources);
+                }
+            }
+
+            // Warn the user about what will be deleted
+            let report_pos = rect.point() + vec2(54.0, 72.0);
+            match self.resize_size() {
+                None => render_text("Invalid size!", WHITE, report_pos, Vec2::ONE, Align::End, Font::Small, resources),
+                Some((width, height)) => {
+                    let clip = editor_level_pack.editor_level().resize_clip(width, height, self.resize_anchor);
+                    let lines: Vec<String> = [
+                        (clip.tiles, "tile"), (clip.entities, "entity"), (clip.signs, "sign"), (clip.doors, "door"), (clip.checkpoints, "checkpoint"),
+                    ]
+                        .iter()
+                        .filter(|(n, _)| *n != 0)
+                        .map(|(n, name)| match (*n, *name) {
+                            (1, name) => format!("1 {name}"),
+                            (n, "entity") => format!("{n} entities"),
+                            (n, name) => format!("{n} {name}s"),
+                        })
+                        .collect();
+                    let header = match lines.is_empty() {
+                        true  => "Nothing deleted",
+                        false => "This will delete:",
+                    };
+                    render_text(header, WHITE, report_pos, Vec2::ONE, Align::End, Font::Small, resources);
+                    for (i, line) in lines.iter().enumerate() {
+                        render_text(line, WHITE, report_pos + vec2(4.0, 10.0 + i as f32 * 10.0), Vec2::ONE, Align::End, Font::Small, resources);
+                    }
+                }
+            }
+            self.resize_popup_cancel.draw(resources);
+            self.resize_popup_resize.draw(resources);
+        } else if self.popup == PopupKind::Replace {
+            // Wider than the other popups so the longer object names fit
+            let rect = Rect::new(VIEW_SIZE.x / 2.0 - 136.0, 58.0, 272.0, 93.0);
+            draw_rect(rect, GRAY);
+            draw_rect_lines(rect, BLACK);
+            render_text("Find and replace", WHITE, rect.point() + vec2(4.0, 3.0), Vec2::ONE, Align::End, Font::Small, resources);
+            for (label, button, object) in [("Find:", &self.replace_popup_find, &self.replace_find), ("With:", &self.replace_popup_with, &self.replace_with)] {
+                let pos = button.rect().point();
+                render_text(label, WHITE, vec2(rect.x + 4.0, pos.y + 4.0), Vec2::ONE, Align::End, Font::Small, resources);
+                button.draw(resources);
+                let name = match object {
+                    Some((Object::Tile(t), _)) => {
+                        render_tile(&TileRenderData { tile: *t, draw_kind: TileDrawKind::Single(0), pos }, Vec2::ZERO, TileRenderLayer::Foreground(1.0), resources);
+                        resources.tile_data(*t).name()
+                    }
+                    Some((Object::Entity(e), name)) => {
+                        e.draw_editor(1.0, false, pos, Vec2::ZERO, resources);
+                        name
+                    }
+                    _ => "(click to pick)",
+                };
+                render_text(name, WHITE, pos + vec2(20.0, 4.0), Vec2::ONE, Align::End, Font::Small, resources);
+            }
+            self.replace_popup_layer.draw(resources);
+            self.replace_popup_scope.draw(resources);
+            self.replace_popup_cancel.draw(resources);
+            self.replace_popup_replace.draw(resources);
+        } else if self.popup == PopupKind::Import {
+            let rect = Rect::new((VIEW_SIZE.x - TEXT_INPUT_RECT.w) / 2.0 - 4.0, 30.0, TEXT_INPUT_RECT.w + 8.0, 176.0);
+            draw_rect(rect, GRAY);
+            draw_rect_lines(rect, BLACK);
+            render_text("Import levels", WHITE, rect.point() + vec2(4.0, 3.0), Vec2::ONE, Align::End, Font::Small, resources);
+            self.import_popup_file_name_input.draw(&self.import_file_name, "File name", resources);
+            self.import_popup_load.draw(resources);
+            self.import_popup_png.draw(resources);
+            self.import_popup_tiled.draw(resources);
+            self.import_popup_cancel.draw(resources);
+
+            if let Some(pack) = &self.import_pack {
+                render_text(pack.name(), WHITE, rect.point() + vec2(4.0, 44.0), Vec2::ONE, Align::End, Font::Small, resources);
+                // The levels on this page, with a little marker if they're selected
+                for (i, b) in self.import_popup_levels.iter().enumerate() {
+                    let index = self.import_page * IMPORT_PAGE_LEN + i;
+                    let level = match pack.levels().get(index) {
+                        Some(l) => l,
+                        None => break,
+                    };
+                    b.draw(resources);
+                    let marker = match self.import_selected.get(index) {
+                        Some(true) => "▪",
+                        _ => " ",
+                    };
+                    // Cut the name short so it fits in the button
+                    let name: String = level.name().chars().take(MAX_USER_STRING_LEN - 5).collect();
+                    render_text(&format!("{} {:0>2} {}", marker, index + 1, name), WHITE, b.rect().point() + vec2(3.0, 1.0), Vec2::ONE, Align::End, Font::Small, resources);
+                }
+                let page_count = pack.levels().len().div_ceil(IMPORT_PAGE_LEN);
+                render_text(&format!("{}/{}", self.import_page + 1, page_count), WHITE, vec2(VIEW_SIZE.x / 2.0, 176.0), Vec2::ONE, Align::Mid, Font::Small, resources);
+                self.import_popup_prev.draw(resources);
+                self.import_popup_next.draw(resources);
+                self.import_popup_import.draw(resources);
+            }
         }
     }
 
@@ -594,6 +1205,8 @@ impl EditorMenu {
         self.pack_level_name_input.draw(editor_level_pack.editor_level().name(), "Level name", resources);
         self.pack_add.draw(resources);
         self.pack_del.draw(resources);
+        self.pack_resize.draw(resources);
+        self.pack_export.draw(resources);
         self.pack_prev.draw(resources);
         self.pack_next.draw(resources);
         self.pack_shift_prev.draw(resources);
@@ -616,6 +1229,8 @@ impl EditorMenu {
 
         self.help_button.draw(resources);
         self.save_button.draw(resources);
+        self.import_button.draw(resources);
+        self.replace_button.draw(resources);
         self.exit_button.draw(resources);
 
         // Draw the popup in front if it's active
@@ -0,0 +1,69 @@
+// Colours each tile by how many times the player has died there, so unfair bits of a level stand out
+
+use std::collections::HashMap;
+
+use macroquad::{color::{Color, BLACK, GRAY, WHITE}, math::{vec2, Rect, Vec2}};
+
+use crate::{editor::editor_level::EditorLevel, game::death_map::{Death, DeathCause}, resources::Resources, text_renderer::{render_text, Align, Font}, util::{draw_rect, draw_rect_lines}, VIEW_SIZE};
+
+const PANEL_WIDTH: f32 = 140.0;
+// Only this many causes are listed when hovering a tile
+const MAX_CAUSES: usize = 6;
+
+// The tile each death happened in, falling deaths are put on the bottom row
+fn death_tile(death: &Death, editor_level: &EditorLevel) -> (usize, usize) {
+    let x = (death.pos.x / 16.0).max(0.0) as usize;
+    let y = (death.pos.y / 16.0).max(0.0) as usize;
+    (x.min(editor_level.width().saturating_sub(1)), y.min(editor_level.height().saturating_sub(1)))
+}
+
+pub fn draw_death_heatmap(deaths: &[Death], editor_level: &EditorLevel, camera_pos: Vec2, cursor_pos: Option<Vec2>, resources: &Resources) {
+    let mut tiles: HashMap<(usize, usize), Vec<DeathCause>> = HashMap::new();
+    for death in deaths {
+        tiles.entry(death_tile(death, editor_level)).or_default().push(death.cause);
+    }
+    let max = tiles.values().map(|c| c.len()).max().unwrap_or(1) as f32;
+
+    // Goes from a faint yellow for the odd death to a strong red for the worst tile
+    for ((x, y), causes) in &tiles {
+        let t = causes.len() as f32 / max;
+        let pos = vec2(*x as f32, *y as f32) * 16.0 - camera_pos;
+        draw_rect(Rect::new(pos.x, pos.y, 16.0, 16.0), Color::new(1.0, 1.0 - t, 0.0, 0.25 + t * 0.4));
+        if causes.len() < 100 {
+            render_text(&format!("{}", causes.len()), WHITE, pos + 8.0, Vec2::ONE, Align::Mid, Font::Small, resources);
+        }
+    }
+
+    render_text(&format!("Deaths: {} (M, shift+M clears)", deaths.len()), WHITE, vec2(VIEW_SIZE.x / 2.0, 18.0), Vec2::ONE, Align::Mid, Font::Small, resources);
+
+    // List what killed the player in the hovered tile, most common first
+    let Some(cursor_pos) = cursor_pos else {
+        return;
+    };
+    let hovered = ((cursor_pos.x / 16.0) as usize, (cursor_pos.y / 16.0) as usize);
+    let Some(causes) = tiles.get(&hovered) else {
+        return;
+    };
+    let mut counts: Vec<(DeathCause, usize)> = vec![];
+    for cause in causes {
+        match counts.iter_mut().find(|(c, _)| c == cause) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((*cause, 1)),
+        }
+    }
+    counts.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+
+    let lines = counts.len().min(MAX_CAUSES);
+    let screen_pos = cursor_pos - camera_pos;
+    let x = match screen_pos.x < VIEW_SIZE.x / 2.0 {
+        true  => screen_pos.x + 20.0,
+        false => screen_pos.x - PANEL_WIDTH - 4.0,
+    };
+    let h = 5.0 + lines as f32 * 10.0;
+    let rect = Rect::new(x, screen_pos.y.min(VIEW_SIZE.y - h - 16.0), PANEL_WIDTH, h);
+    draw_rect(rect, GRAY);
+    draw_rect_lines(rect, BLACK);
+    for (i, (cause, count)) in counts.iter().take(MAX_CAUSES).enumerate() {
+        render_text(&format!("{} x{}", cause.name(), count), WHITE, rect.point() + vec2(4.0, 3.0 + i as f32 * 10.0), Vec2::ONE, Align::End, Font::Small, resources);
+    }
+}
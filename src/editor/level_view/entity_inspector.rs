@@ -0,0 +1,161 @@
+// A popup for changing the props of an entity that's been placed (how fast it goes, which way it faces, etc.)
+
+use macroquad::{color::{Color, BLACK, DARKGRAY, GRAY, ORANGE, RED, WHITE}, color_u8, input::{is_key_down, KeyCode}, math::{vec2, Rect, Vec2}, shapes::draw_rectangle};
+
+use crate::{game::entity::{props::{EntityProps, TimingState}, EntityKind}, resources::Resources, text_renderer::{render_text, Align, Font}, ui::{button::Button, Ui}, util::{draw_rect, draw_rect_lines}, VIEW_SIZE};
+
+const BG_COL: Color = color_u8!(255, 255, 255, 100);
+const WIDTH: f32 = 188.0;
+const ROW_HEIGHT: f32 = 14.0;
+// The timeline for timed entities shows this many of the entities in the group
+const TIMELINE_ROWS: usize = 6;
+const TIMELINE_ROW_HEIGHT: f32 = 7.0;
+const TIMELINE_HEIGHT: f32 = TIMELINE_ROWS as f32 * TIMELINE_ROW_HEIGHT + 4.0;
+
+#[derive(PartialEq, Eq)]
+pub enum EntityInspectorReturn {
+    None,
+    Cancel,
+    Done,
+}
+
+pub struct EntityInspector {
+    pos: Vec2,
+    kind: EntityKind,
+    name: String,
+    props: EntityProps,
+    // All of the other timed entities in the level, for showing the group on the timeline
+    others: Vec<(EntityKind, EntityProps)>,
+    // The (-, +) buttons for each prop
+    step_buttons: Vec<(Button, Button)>,
+    done: Button,
+    cancel: Button,
+}
+
+impl EntityInspector {
+    pub fn new(pos: Vec2, kind: EntityKind, props: EntityProps, name: String, others: Vec<(EntityKind, EntityProps)>) -> Self {
+        let rect = Self::rect(kind);
+        let step_buttons = (0..kind.props().len()).map(|i| {
+            let y = rect.y + 16.0 + i as f32 * ROW_HEIGHT;
+            (
+                Button::new(Rect::new(rect.x + 100.0, y, 12.0, 12.0), Some(String::from("-")), None),
+                Button::new(Rect::new(rect.x + 172.0, y, 12.0, 12.0), Some(String::from("+")), None),
+            )
+        }).collect();
+        let buttons_y = rect.y + rect.h + 3.0;
+
+        Self {
+            pos,
+            kind,
+            name,
+            props,
+            others,
+            step_buttons,
+            done:   Button::new(Rect::new(VIEW_SIZE.x/2.0 + 2.0,  buttons_y, 54.0, 12.0), Some(String::from("Done")), None),
+            cancel: Button::new(Rect::new(VIEW_SIZE.x/2.0 - 56.0, buttons_y, 54.0, 12.0), Some(String::from("Cancel")), None),
+        }
+    }
+
+    pub fn data(self) -> (Vec2, EntityProps) {
+        (self.pos, self.props)
+    }
+
+    fn rect(kind: EntityKind) -> Rect {
+        let mut h = 18.0 + kind.props().len() as f32 * ROW_HEIGHT;
+        if kind.timed() {
+            h += TIMELINE_HEIGHT;
+        }
+        Rect::new((VIEW_SIZE.x - WIDTH) / 2.0, (VIEW_SIZE.y - h) / 2.0 - 8.0, WIDTH, h)
+    }
+
+    pub fn update(&mut self, ui: &mut Ui) -> EntityInspectorReturn {
+        // Holding shift changes things quicker
+        let amount = match is_key_down(KeyCode::LeftShift) {
+            true  => 10,
+            false => 1,
+        };
+        for (prop, (minus, plus)) in self.kind.props().iter().zip(self.step_buttons.iter_mut()) {
+            minus.update(ui);
+            plus.update(ui);
+            if minus.released() {
+                prop.step(self.kind, &mut self.props, -amount);
+            }
+            if plus.released() {
+                prop.step(self.kind, &mut self.props, amount);
+            }
+        }
+
+        self.done.update(ui);
+        self.cancel.update(ui);
+
+        if self.done.released()   { return EntityInspectorReturn::Done; }
+        if self.cancel.released() { return EntityInspectorReturn::Cancel; }
+        EntityInspectorReturn::None
+    }
+
+    pub fn draw(&self, resources: &Resources) {
+        draw_rectangle(0.0, 0.0, VIEW_SIZE.x, VIEW_SIZE.y, BG_COL);
+
+        let rect = Self::rect(self.kind);
+        draw_rect(rect, GRAY);
+        draw_rect_lines(rect, BLACK);
+        render_text(&self.name, WHITE, rect.point() + vec2(4.0, 3.0), Vec2::ONE, Align::End, Font::Small, resources);
+
+        for (prop, (minus, plus)) in self.kind.props().iter().zip(self.step_buttons.iter()) {
+            let y = minus.rect().y + 2.0;
+            render_text(prop.name(self.kind), WHITE, vec2(rect.x + 4.0, y), Vec2::ONE, Align::End, Font::Small, resources);
+            render_text(&prop.value_text(self.kind, &self.props), WHITE, vec2(rect.x + 142.0, y + 4.0), Vec2::ONE, Align::Mid, Font::Small, resources);
+            minus.draw(resources);
+            plus.draw(resources);
+        }
+
+        if self.kind.timed() {
+            self.draw_timeline(Rect::new(rect.x + 4.0, rect.y + rect.h - TIMELINE_HEIGHT - 2.0, rect.w - 8.0, TIMELINE_HEIGHT), resources);
+        }
+
+        self.done.draw(resources);
+        self.cancel.draw(resources);
+    }
+
+    // Shows two cycles of this entity and the others in its group, with a line moving along it
+    fn draw_timeline(&self, rect: Rect, resources: &Resources) {
+        draw_rect(rect, BLACK);
+
+        // Everything in the group will have the same period once this is done
+        let period = self.props.period_or_default(self.kind);
+        let group = self.others
+            .iter()
+            .filter(|_| self.props.group != 0)
+            .filter(|(_, p)| p.group == self.props.group)
+            .map(|(k, p)| (*k, EntityProps { period, ..*p }));
+        let rows = std::iter::once((self.kind, self.props)).chain(group).take(TIMELINE_ROWS);
+
+        let window = 2.0 * self.props.period().unwrap_or(self.kind.default_period());
+        let width = rect.w - 2.0;
+
+        for (i, (kind, props)) in rows.enumerate() {
+            let y = rect.y + 2.0 + i as f32 * TIMELINE_ROW_HEIGHT;
+            // Draw each run of the same state as one rect
+            let state_at = |x: f32| kind.timing_state(&props, x as f64 / width as f64 * window);
+            let mut run_start = 0.0;
+            for x in 1..=width as usize {
+                let x = x as f32;
+                if x < width && state_at(x) == state_at(run_start) {
+                    continue;
+                }
+                let color = match state_at(run_start) {
+                    TimingState::Off     => DARKGRAY,
+                    TimingState::Warning => ORANGE,
+                    TimingState::On      => RED,
+                };
+                draw_rect(Rect::new(rect.x + 1.0 + run_start, y, x - run_start, TIMELINE_ROW_HEIGHT - 2.0), color);
+                run_start = x;
+            }
+        }
+        // This entity is always the first one
+        draw_rect_lines(Rect::new(rect.x + 1.0, rect.y + 2.0, width, TIMELINE_ROW_HEIGHT - 2.0), WHITE);
+
+        let playhead = (resources.tile_animation_timer() % window) / window * width as f64;
+        draw_rect(Rect::new(rect.x + 1.0 + playhead as f32, rect.y + 1.0, 1.0, rect.h - 2.0), WHITE);
+    }
+}
@@ -0,0 +1,133 @@
+// Shows how far the player can jump from a tile, so gaps can be checked without having to playtest them all the time
+// The arcs are worked out by stepping through the same physics the player uses, holding jump and the direction the whole time
+
+use macroquad::{color::{Color, ORANGE, WHITE}, math::{vec2, Rect, Vec2}, shapes::draw_line};
+
+use crate::{editor::editor_level::EditorLevel, game::{level::tile::{Tile, TileCollision}, player::{FeetPowerup, Player, PowerupKind, State, AIR_APPROACH, AIR_SPEED, JUMP_VEL, RUN_SPEED_END}}, resources::Resources, text_renderer::{render_text, Align, Font}, util::{approach_target, draw_rect_lines}, VIEW_SIZE};
+
+// Stop simulating after this many physics steps (the skirt can glide for ages)
+const MAX_STEPS: usize = 120 * 8;
+// Where the arcs are drawn from on the player
+const FEET: Vec2 = vec2(8.0, 16.0);
+
+#[derive(Default)]
+pub struct JumpReach {
+    // The tile the player jumps from
+    origin: Option<Vec2>,
+    feet_powerup: Option<FeetPowerup>,
+    arcs: Vec<(Color, Vec<Vec2>)>,
+}
+
+impl JumpReach {
+    pub fn active(&self) -> bool {
+        self.origin.is_some()
+    }
+    // Jumping from a tile, or turning it off if it's the same tile
+    pub fn toggle(&mut self, pos: Vec2) {
+        self.origin = match self.origin == Some(pos) {
+            true  => None,
+            false => Some(pos),
+        };
+    }
+    pub fn clear(&mut self) {
+        self.origin = None;
+        self.arcs.clear();
+    }
+    pub fn cycle_feet_powerup(&mut self) {
+        self.feet_powerup = match self.feet_powerup {
+            None => Some(FeetPowerup::Boots),
+            Some(FeetPowerup::Boots) => Some(FeetPowerup::MoonShoes),
+            Some(FeetPowerup::MoonShoes) => Some(FeetPowerup::Skirt),
+            Some(FeetPowerup::Skirt) => None,
+        };
+    }
+
+    // The level could've changed, so this is worked out every frame
+    pub fn update(&mut self, editor_level: &EditorLevel, resources: &Resources) {
+        self.arcs.clear();
+        let Some(origin) = self.origin else {
+            return;
+        };
+        let running_color = match self.feet_powerup {
+            Some(p) => PowerupKind::Feet(p).text_color(),
+            None => ORANGE,
+        };
+        for dir in [-1.0, 1.0] {
+            self.arcs.push((WHITE,         simulate(origin, dir, false, self.feet_powerup, editor_level, resources)));
+            self.arcs.push((running_color, simulate(origin, dir, true,  self.feet_powerup, editor_level, resources)));
+        }
+    }
+
+    pub fn draw(&self, camera_pos: Vec2, resources: &Resources) {
+        let Some(origin) = self.origin else {
+            return;
+        };
+        draw_rect_lines(Rect::new(origin.x - camera_pos.x, origin.y - camera_pos.y, 16.0, 16.0), ORANGE);
+        for (color, points) in &self.arcs {
+            for p in points.windows(2) {
+                let (a, b) = (p[0] - camera_pos, p[1] - camera_pos);
+                draw_line(a.x, a.y, b.x, b.y, 1.0, *color);
+            }
+        }
+
+        let powerup = match self.feet_powerup {
+            Some(p) => PowerupKind::Feet(p).name().to_owned(),
+            None => String::from("No powerup"),
+        };
+        render_text(&format!("Jump reach: {powerup} (K)"), WHITE, vec2(VIEW_SIZE.x / 2.0, 8.0), Vec2::ONE, Align::Mid, Font::Small, resources);
+    }
+}
+
+// The tile at a position, if it's in the level
+fn tile_at(pos: Vec2, editor_level: &EditorLevel) -> Option<Tile> {
+    if pos.x < 0.0 || pos.y < 0.0 {
+        return None;
+    }
+    let (x, y) = ((pos.x / 16.0) as usize, (pos.y / 16.0) as usize);
+    if x >= editor_level.width() || y >= editor_level.height() {
+        return None;
+    }
+    editor_level.tiles().get(y * editor_level.width() + x).copied()
+}
+
+// Returns the path of the player's feet until they land, hit something, or leave the level
+fn simulate(origin: Vec2, dir: f32, running: bool, feet_powerup: Option<FeetPowerup>, editor_level: &EditorLevel, resources: &Resources) -> Vec<Vec2> {
+    let mut pos = origin;
+    let mut vel = Vec2::ZERO;
+    if running {
+        vel.x = RUN_SPEED_END * dir;
+    }
+    // Running makes the player jump higher
+    vel.y = -(JUMP_VEL + vel.x.abs() / 4.0);
+    let mut target_x_vel = vel.x;
+    let mut state = State::Jumping;
+
+    let mut points = vec![pos + FEET];
+    let level_bottom = editor_level.height() as f32 * 16.0;
+
+    for _ in 0..MAX_STEPS {
+        // This is the same as the player's air logic (with the direction held) and physics update
+        if vel.x * dir < AIR_SPEED {
+            target_x_vel = AIR_SPEED * dir;
+        }
+        let (gravity, max_fall_speed) = Player::gravity(feet_powerup, state, true);
+        vel.y = (vel.y + gravity).min(max_fall_speed);
+        pos += vel;
+        approach_target(&mut vel.x, AIR_APPROACH, target_x_vel);
+        if vel.y >= 0.0 && state == State::Jumping {
+            state = State::Falling;
+        }
+        points.push(pos + FEET);
+
+        // Hitting something solid, or landing on a platform, ends the jump
+        let collision = |p: Vec2| tile_at(pos + p, editor_level).map(|t| *resources.tile_data(t).collision());
+        let hit_solid = Player::collision_points().iter().any(|p| matches!(collision(*p), Some(TileCollision::Solid { .. })));
+        let landed_on_platform = vel.y > 0.0
+            && matches!(collision(FEET), Some(TileCollision::Platform))
+            && (pos.y + FEET.y) % 16.0 <= vel.y;
+        if hit_solid || landed_on_platform || pos.y > level_bottom {
+            break;
+        }
+    }
+    points
+}
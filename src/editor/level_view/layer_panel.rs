@@ -0,0 +1,137 @@
+// A little panel for showing/hiding, fading and locking each layer of the level, so one can be edited without messing up the others
+
+use macroquad::{color::{BLACK, GRAY, WHITE}, math::{vec2, Rect, Vec2}};
+
+use crate::{resources::Resources, text_renderer::{render_text, Align, Font}, ui::{button::Button, Ui}, util::{draw_rect, draw_rect_lines}};
+
+const PANEL_RECT: Rect = Rect { x: 231.0, y: 109.0, w: 120.0, h: 99.0 };
+// Percentages, clicking a layer's opacity button goes through these
+const OPACITIES: [u8; 3] = [100, 50, 25];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EditorLayer {
+    Foreground, Background, Entities, Signs, Doors, Checkpoints,
+}
+
+impl EditorLayer {
+    pub const ALL: [EditorLayer; 6] = [Self::Foreground, Self::Background, Self::Entities, Self::Signs, Self::Doors, Self::Checkpoints];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Foreground  => "FG tiles",
+            Self::Background  => "BG tiles",
+            Self::Entities    => "Entities",
+            Self::Signs       => "Signs",
+            Self::Doors       => "Doors",
+            Self::Checkpoints => "Checkpts",
+        }
+    }
+}
+
+pub struct LayerPanel {
+    active: bool,
+    // Indexed by EditorLayer
+    visible: [bool; 6],
+    locked:  [bool; 6],
+    opacity: [u8; 6],
+
+    open_button: Button,
+    visible_buttons: Vec<Button>,
+    locked_buttons:  Vec<Button>,
+    opacity_buttons: Vec<Button>,
+}
+
+impl Default for LayerPanel {
+    fn default() -> Self {
+        let row_button = |i: usize, x: f32, w: f32, label: Option<String>, tooltip: &str| {
+            Button::new(Rect::new(PANEL_RECT.x + x, PANEL_RECT.y + 14.0 + i as f32 * 14.0, w, 12.0), label, Some(tooltip.to_owned()))
+        };
+        Self {
+            active: false,
+            visible: [true; 6],
+            locked: [false; 6],
+            opacity: [100; 6],
+
+            open_button: Button::new(Rect::new(259.0, 211.0, 48.0, 12.0), Some("Layers".to_owned()), Some("Show/lock layers".to_owned())),
+            visible_buttons: (0..6).map(|i| row_button(i, 84.0, 12.0, None, "Show/hide")).collect(),
+            locked_buttons:  (0..6).map(|i| row_button(i, 102.0, 12.0, None, "Lock/unlock")).collect(),
+            opacity_buttons: (0..6).map(|i| row_button(i, 58.0, 22.0, Some(String::from("100")), "Opacity")).collect(),
+        }
+    }
+}
+
+impl LayerPanel {
+    pub fn visible(&self, layer: EditorLayer) -> bool {
+        self.visible[layer as usize]
+    }
+    pub fn opacity(&self, layer: EditorLayer) -> f32 {
+        self.opacity[layer as usize] as f32 / 100.0
+    }
+    // Hidden layers can't be edited either, since you can't see what you're doing!
+    pub fn editable(&self, layer: EditorLayer) -> bool {
+        self.visible[layer as usize] && !self.locked[layer as usize]
+    }
+    // For things that change every layer at once, like resizing
+    pub fn all_editable(&self) -> bool {
+        EditorLayer::ALL.iter().all(|l| self.editable(*l))
+    }
+
+    pub fn update(&mut self, ui: &mut Ui) {
+        self.open_button.update(ui);
+        if self.open_button.released() {
+            self.active = !self.active;
+        }
+        if !self.active {
+            return;
+        }
+
+        for i in 0..EditorLayer::ALL.len() {
+            self.visible_buttons[i].update(ui);
+            self.locked_buttons[i].update(ui);
+            self.opacity_buttons[i].update(ui);
+            if self.visible_buttons[i].released() {
+                self.visible[i] = !self.visible[i];
+            }
+            if self.locked_buttons[i].released() {
+                self.locked[i] = !self.locked[i];
+            }
+            if self.opacity_buttons[i].released() {
+                let next = OPACITIES.iter().position(|o| *o == self.opacity[i]).map_or(0, |o| (o + 1) % OPACITIES.len());
+                self.opacity[i] = OPACITIES[next];
+                self.opacity_buttons[i].set_label(self.opacity[i].to_string());
+            }
+        }
+        // Stop the level from being edited through the panel
+        if Ui::mouse_pos().is_some_and(|m| PANEL_RECT.contains(m)) {
+            ui.interact();
+        }
+    }
+
+    pub fn draw(&self, resources: &Resources) {
+        self.open_button.draw(resources);
+        if !self.active {
+            return;
+        }
+
+        draw_rect(PANEL_RECT, GRAY);
+        draw_rect_lines(PANEL_RECT, BLACK);
+        render_text("Layers", WHITE, PANEL_RECT.point() + vec2(4.0, 3.0), Vec2::ONE, Align::End, Font::Small, resources);
+        // Opacity, show and lock
+        render_text("%", WHITE, PANEL_RECT.point() + vec2( 69.0, 7.0), Vec2::ONE, Align::Mid, Font::Small, resources);
+        render_text("S", WHITE, PANEL_RECT.point() + vec2( 90.0, 7.0), Vec2::ONE, Align::Mid, Font::Small, resources);
+        render_text("L", WHITE, PANEL_RECT.point() + vec2(108.0, 7.0), Vec2::ONE, Align::Mid, Font::Small, resources);
+
+        for (i, layer) in EditorLayer::ALL.iter().enumerate() {
+            let (visible_button, locked_button) = (&self.visible_buttons[i], &self.locked_buttons[i]);
+            render_text(layer.name(), WHITE, vec2(PANEL_RECT.x + 4.0, visible_button.rect().y + 2.0), Vec2::ONE, Align::End, Font::Small, resources);
+            visible_button.draw(resources);
+            locked_button.draw(resources);
+            self.opacity_buttons[i].draw(resources);
+            for (button, on) in [(visible_button, self.visible[i]), (locked_button, self.locked[i])] {
+                if on {
+                    render_text("▪", WHITE, button.rect().center(), Vec2::ONE, Align::Mid, Font::Small, resources);
+                }
+            }
+        }
+    }
+}
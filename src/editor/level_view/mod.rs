@@ -1,17 +1,32 @@
 // This is the 'level view', it lets the user actually edit an editor level
 
+use death_heatmap::draw_death_heatmap;
 use editor_camera::EditorCamera;
+use entity_inspector::{EntityInspector, EntityInspectorReturn};
 use macroquad::{color::{Color, ORANGE, PURPLE, WHITE}, color_u8, input::{is_key_down, is_key_pressed, is_mouse_button_down, is_mouse_button_pressed, mouse_delta_position, KeyCode, MouseButton}, math::{vec2, Rect, Vec2}, shapes::{draw_line, draw_rectangle, draw_rectangle_lines}};
+use jump_reach::JumpReach;
+use layer_panel::{EditorLayer, LayerPanel};
 use object_selector::{Object, ObjectSelector, ObjectOtherKind};
+use playtest_panel::{PlaytestOptions, PlaytestPanel};
 use sign_popup::{SignPopup, SignPopupReturn};
+use tile_inspector::draw_tile_inspector;
 
-use crate::{game::level::{things::DoorKind, tile::{render_tile, Tile, TileRenderLayer}, Level, TileDrawKind, TileRenderData}, resources::Resources, ui::{button::Button, toast::ToastManager, Ui}, util::draw_rect, VIEW_HEIGHT, VIEW_SIZE};
+use crate::{game::{death_map::Death, level::{things::DoorKind, tile::{render_tile, Tile, TileRenderLayer}, Level, TileDrawKind, TileRenderData}}, resources::Resources, ui::{button::Button, toast::{ToastKind, ToastManager}, Ui}, util::draw_rect, VIEW_HEIGHT, VIEW_SIZE};
 
-use super::{editor_level::EditorLevel, editor_menu::{EditorMenu, HelpKind}};
+use super::{editor_level::{EditorLevel, LevelTransform}, editor_menu::{EditorMenu, HelpKind}};
 
+pub mod death_heatmap;
 pub mod editor_camera;
+pub mod entity_inspector;
+pub mod jump_reach;
+pub mod layer_panel;
 pub mod object_selector;
+pub mod playtest_panel;
 pub mod sign_popup;
+pub mod tile_inspector;
+
+// How see-through the foreground is when editing the background
+const OTHER_LAYER_OPACITY: f32 = 0.25;
 
 pub enum SignClipboard {
     None,
@@ -32,6 +47,10 @@ pub struct LevelView {
     layer_switch_button: Button,
     // Could be an enum but meh
     layer_bg: bool,
+    // For showing/hiding and locking layers
+    layer_panel: LayerPanel,
+    // For choosing what the player starts with when playtesting
+    playtest_panel: PlaytestPanel,
 
     // The object selector
     object_selector: ObjectSelector,
@@ -39,9 +58,21 @@ pub struct LevelView {
     sign_popup: Option<SignPopup>,
     // The data of the cut/copied sign
     sign_clipboard: SignClipboard,
+    // The popup for changing an entity's props
+    entity_inspector: Option<EntityInspector>,
+    // If the panel showing the hovered tile's data is shown
+    tile_inspector: bool,
+    // The arcs showing how far the player can jump
+    jump_reach: JumpReach,
+    // If the heatmap of where the player has died is shown, and if the deaths should be cleared
+    death_heatmap: bool,
+    clear_deaths: bool,
 
     // The position of the player to be spawned in when the player wants to test their level
     test_spawn_point: Option<(Vec2, bool)>,    
+    // The corners of the selected area (for mirroring/rotating), and if it's still being dragged
+    selection: Option<(Vec2, Vec2)>,
+    selecting: bool,
 
     camera: EditorCamera,
 }
@@ -65,11 +96,20 @@ impl LevelView {
             resize_buttons: resize_buttons.try_into().unwrap_or_else(|_| panic!("this will literally never happen lol :P")),
             layer_switch_button: Button::new(Rect::new(311.0, 211.0, 40.0, 12.0), Some("FG".to_owned()), Some("Toggle draw layer".to_owned())),
             layer_bg: false,
+            layer_panel: LayerPanel::default(),
+            playtest_panel: PlaytestPanel::default(),
 
             object_selector: ObjectSelector::new(resources),
             sign_popup: None,
             sign_clipboard: SignClipboard::None,
+            entity_inspector: None,
+            tile_inspector: false,
+            jump_reach: JumpReach::default(),
+            death_heatmap: false,
+            clear_deaths: false,
             test_spawn_point: None,
+            selection: None,
+            selecting: false,
             camera: EditorCamera::default(),
         }
     }
@@ -77,13 +117,26 @@ impl LevelView {
     pub fn test_spawn_point(&self) -> Option<(Vec2, bool)> {
         self.test_spawn_point
     }
+    // If the deaths in the current level should be cleared, only returns true once
+    pub fn take_clear_deaths(&mut self) -> bool {
+        std::mem::take(&mut self.clear_deaths)
+    }
+    pub fn playtest_options(&self) -> &PlaytestOptions {
+        self.playtest_panel.options()
+    }
     pub fn clear_test_spawn_point(&mut self) {
         self.test_spawn_point = None;
     }
 
+    pub fn layer_panel(&self) -> &LayerPanel {
+        &self.layer_panel
+    }
+
     pub fn reset_camera(&mut self) {
         self.camera.reset_pos();
         self.clear_cursor();
+        self.selection = None;
+        self.jump_reach.clear();
     }
     pub fn clear_cursor(&mut self) {
         self.cursor_pos = None;
@@ -95,12 +148,52 @@ impl LevelView {
     pub fn sign_popup_open(&self) -> bool {
         self.sign_popup.is_some()
     }
+    pub fn open_object_selector(&mut self) {
+        self.object_selector.set_active(true);
+    }
     pub fn close_object_selector(&mut self) {
         self.object_selector.set_active(false);
     }
     pub fn close_sign_popup(&mut self) {
         self.sign_popup = None;
     }
+    pub fn entity_inspector_open(&self) -> bool {
+        self.entity_inspector.is_some()
+    }
+    pub fn close_entity_inspector(&mut self) {
+        self.entity_inspector = None;
+    }
+
+    // The selected area in pixels
+    fn selection_rect(&self) -> Option<Rect> {
+        self.selection.map(|(a, b)| {
+            let min = a.min(b);
+            Rect::new(min.x, min.y, (a.x - b.x).abs() + 16.0, (a.y - b.y).abs() + 16.0)
+        })
+    }
+
+    fn update_transforms(&mut self, editor_level: &mut EditorLevel, toast_manager: &mut ToastManager) {
+        let transform = match (is_key_pressed(KeyCode::H), is_key_pressed(KeyCode::V), is_key_pressed(KeyCode::R)) {
+            (true, _, _) => LevelTransform::MirrorH,
+            (_, true, _) => LevelTransform::MirrorV,
+            (_, _, true) if is_key_down(KeyCode::LeftShift) => LevelTransform::RotateCcw,
+            (_, _, true) => LevelTransform::RotateCw,
+            _ => return,
+        };
+        let area = self.selection_rect();
+        // Hidden/locked layers don't get transformed
+        if !editor_level.transform(transform, area, |l| self.layer_panel.editable(l)) {
+            toast_manager.add_toast(String::from("Can't rotate the level with hidden or locked layers!"), ToastKind::Warning);
+            return;
+        }
+
+        // The selection stays on what was rotated
+        if let (Some(area), true) = (area, transform == LevelTransform::RotateCw || transform == LevelTransform::RotateCcw) {
+            self.selection = Some((area.point(), area.point() + vec2(area.h, area.w) - 16.0));
+        }
+        // Rotating the whole level could move the camera out of bounds
+        self.camera.set_pos(self.camera.pos(), editor_level);
+    }
 
     fn update_resize_buttons(&mut self, editor_level: &mut EditorLevel, ui: &mut Ui) {
         // i KNOW i copy some code here from the draw function... just let me live man
@@ -114,6 +207,9 @@ impl LevelView {
         let h_center = (top_edge.clamp(0.0, VIEW_SIZE.y)  + bot_edge.clamp(0.0, VIEW_SIZE.y))   / 2.0;
         let v_center = (left_edge.clamp(0.0, VIEW_SIZE.x) + right_edge.clamp(0.0, VIEW_SIZE.x)) / 2.0;
 
+        // Moving a border can cut off any of the layers
+        let locked = !self.layer_panel.all_editable();
+
         // This code uses LOTS of repetition.....
         // but I DON'T CARE !!!! it works!!!! 
         if left_edge > 0.0 {
@@ -121,8 +217,8 @@ impl LevelView {
             b.0.set_pos(vec2(left_edge - 13.0, h_center - 7.0));
             b.1.set_pos(vec2(left_edge - 13.0, h_center + 7.0));
 
-            b.0.set_disabled(!editor_level.can_change_width(true));
-            b.1.set_disabled(!editor_level.can_change_width(false));
+            b.0.set_disabled(locked || !editor_level.can_change_width(true));
+            b.1.set_disabled(locked || !editor_level.can_change_width(false));
 
             b.0.update(ui);
             b.1.update(ui);
@@ -139,8 +235,8 @@ impl LevelView {
             b.0.set_pos(vec2(right_edge + 2.0, h_center - 7.0));
             b.1.set_pos(vec2(right_edge + 2.0, h_center + 7.0));
 
-            b.0.set_disabled(!editor_level.can_change_width(true));
-            b.1.set_disabled(!editor_level.can_change_width(false));
+            b.0.set_disabled(locked || !editor_level.can_change_width(true));
+            b.1.set_disabled(locked || !editor_level.can_change_width(false));
 
             b.0.update(ui);
             b.1.update(ui);
@@ -157,8 +253,8 @@ impl LevelView {
             b.0.set_pos(vec2(v_center - 7.0, top_edge - 13.0));
             b.1.set_pos(vec2(v_center + 7.0, top_edge - 13.0));
 
-            b.0.set_disabled(!editor_level.can_change_height(true));
-            b.1.set_disabled(!editor_level.can_change_height(false));
+            b.0.set_disabled(locked || !editor_level.can_change_height(true));
+            b.1.set_disabled(locked || !editor_level.can_change_height(false));
 
             b.0.update(ui);
             b.1.update(ui);
@@ -175,8 +271,8 @@ impl LevelView {
             b.0.set_pos(vec2(v_center - 7.0, bot_edge + 2.0));
             b.1.set_pos(vec2(v_center + 7.0, bot_edge + 2.0));
 
-            b.0.set_disabled(!editor_level.can_change_height(true));
-            b.1.set_disabled(!editor_level.can_change_height(false));
+            b.0.set_disabled(locked || !editor_level.can_change_height(true));
+            b.1.set_disabled(locked || !editor_level.can_change_height(false));
 
             b.0.update(ui);
             b.1.update(ui);
@@ -203,14 +299,47 @@ impl LevelView {
                 }
             }
         }
+        // Update the entity inspector
+        if let Some(e) = &mut self.entity_inspector {
+            match e.update(ui) {
+                EntityInspectorReturn::None => return,
+                EntityInspectorReturn::Cancel => self.entity_inspector = None,
+                EntityInspectorReturn::Done => {
+                    let (pos, props) = self.entity_inspector.take().unwrap().data();
+                    editor_level.set_entity_props(pos, props);
+                }
+            }
+        }
 
         // Toggle the object selector
         if is_key_pressed(KeyCode::Space) {
             self.object_selector.set_active(!self.object_selector.active());
         }
+        // Toggle the tile inspector
+        if is_key_pressed(KeyCode::I) {
+            self.tile_inspector = !self.tile_inspector;
+        }
+        // Toggle the death heatmap, or clear the deaths with shift
+        if is_key_pressed(KeyCode::M) {
+            match is_key_down(KeyCode::LeftShift) {
+                true  => self.clear_deaths = true,
+                false => self.death_heatmap = !self.death_heatmap,
+            }
+        }
+        // Change the feet powerup used for the jump reach
+        if is_key_pressed(KeyCode::K) && self.jump_reach.active() {
+            self.jump_reach.cycle_feet_powerup();
+        }
         if self.object_selector.active() {
             let object = self.object_selector.update(ui);
             // If the user clicked on something, choose it and close the menu
+            // If the menu is waiting for an object (for find and replace), give it to that instead
+            if let (Some(object), true) = (object, editor_menu.picking_object()) {
+                let name = self.object_selector.object_name(object).cloned().unwrap_or_default();
+                editor_menu.set_picked_object(object, name);
+                self.object_selector.set_active(false);
+                return;
+            }
             if let Some(object) = object {
                 editor_level.set_door_start(None);
                 self.test_spawn_point = None;
@@ -254,11 +383,23 @@ impl LevelView {
             });
         }
 
+        self.layer_panel.update(ui);
+        self.playtest_panel.update(ui);
+
         // Resizing the level with buttons
         self.update_resize_buttons(editor_level, ui);
 
+        // Mirroring/rotating the selection (or the level) with ctrl
+        let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+        if ctrl {
+            self.update_transforms(editor_level, toast_manager);
+        }
+        if !is_mouse_button_down(MouseButton::Left) {
+            self.selecting = false;
+        }
+
         // Opening the help menu
-        if is_key_pressed(KeyCode::H) {
+        if is_key_pressed(KeyCode::H) && !ctrl {
             let help_kind = match self.selected_object {
                 Object::Tile(_)   => HelpKind::Tiles,
                 Object::Entity(_) => HelpKind::Entities,
@@ -294,8 +435,24 @@ impl LevelView {
     
                 let copy_pressed = is_key_pressed(KeyCode::C);
                 let cut_pressed = is_key_pressed(KeyCode::X);
+
+                // If the layer of the selected object is hidden or locked, it can't be edited
+                let object_layer = match self.selected_object {
+                    Object::Tile(_) if self.layer_bg => Some(EditorLayer::Background),
+                    Object::Tile(_)   => Some(EditorLayer::Foreground),
+                    Object::Entity(_) => Some(EditorLayer::Entities),
+                    Object::Other(ObjectOtherKind::Sign)       => Some(EditorLayer::Signs),
+                    Object::Other(ObjectOtherKind::Door(_))    => Some(EditorLayer::Doors),
+                    Object::Other(ObjectOtherKind::Checkpoint) => Some(EditorLayer::Checkpoints),
+                    _ => None,
+                };
+                let locked = object_layer.is_some_and(|l| !self.layer_panel.editable(l));
     
                 if let Some(cursor_pos) = self.cursor_pos {
+                    // Show how far the player can jump from here
+                    if is_key_pressed(KeyCode::J) {
+                        self.jump_reach.toggle(cursor_pos);
+                    }
                     // If the user presses tab, let them place the test spawn point
                     if is_key_pressed(KeyCode::Tab) {
                         self.test_spawn_point = match self.test_spawn_point {
@@ -311,6 +468,24 @@ impl LevelView {
                             *placed = true;
                         }
                     }
+                    // Selecting an area by dragging with ctrl held, and clearing it with right click
+                    else if ctrl || self.selecting {
+                        if is_mouse_button_pressed(MouseButton::Left) {
+                            self.selection = Some((cursor_pos, cursor_pos));
+                            self.selecting = true;
+                        } else if is_mouse_button_pressed(MouseButton::Right) {
+                            self.selection = None;
+                        }
+                        if let (Some((_, end)), true) = (&mut self.selection, self.selecting) {
+                            *end = cursor_pos;
+                        }
+                    }
+                    // Don't edit hidden/locked layers
+                    else if locked {
+                        if is_mouse_button_pressed(MouseButton::Left) || is_mouse_button_pressed(MouseButton::Right) {
+                            toast_manager.add_toast(String::from("That layer is hidden or locked!"), ToastKind::Warning);
+                        }
+                    }
                     // If the object is a tile
                     else if let Object::Tile(tile) = self.selected_object {
                         // Drawing
@@ -330,9 +505,20 @@ impl LevelView {
                     }
                     // If the object is an entity
                     else if let Object::Entity(kind) = self.selected_object {
-                        // Placing
+                        // Placing, or inspecting the entity that's already there
                         if is_mouse_button_pressed(MouseButton::Left) {
-                            editor_level.try_add_entity(cursor_pos, kind, toast_manager);
+                            match editor_level.entity_at_pos(cursor_pos) {
+                                Some((kind, props)) if !kind.props().is_empty() => {
+                                    let name = self.object_selector.object_name(Object::Entity(kind)).cloned().unwrap_or_default();
+                                    let others = editor_level.entities()
+                                        .iter()
+                                        .filter(|(p, k, _)| k.timed() && *p != cursor_pos)
+                                        .map(|(_, k, props)| (*k, *props))
+                                        .collect();
+                                    self.entity_inspector = Some(EntityInspector::new(cursor_pos, kind, props, name, others));
+                                }
+                                _ => editor_level.try_add_entity(cursor_pos, kind, toast_manager),
+                            }
                         }
                         // Removing
                         else if is_mouse_button_pressed(MouseButton::Right) {
@@ -454,10 +640,11 @@ impl LevelView {
         }
         
         editor_level.update_if_should(resources);
+        self.jump_reach.update(editor_level, resources);
     }
 
 
-    pub fn draw(&self, editor_level: &EditorLevel, resources: &Resources) {
+    pub fn draw(&self, editor_level: &EditorLevel, deaths: &[Death], resources: &Resources, debug: bool) {
         // Draw the bg
         draw_rect(Rect::new(0.0, 0.0, VIEW_SIZE.x, VIEW_SIZE.y), editor_level.bg_col_as_color());
 
@@ -500,25 +687,38 @@ impl LevelView {
             draw_buttons(3);
         }
 
-        let draw_fg = |transparent: bool| {
-            editor_level.draw_fg(camera_pos, transparent, resources);
+        // Draw the level (only the layers that aren't hidden, as see-through as they've been set to be in the layer panel)
+        let visible = |layer: EditorLayer| self.layer_panel.visible(layer);
+        let opacity = |layer: EditorLayer| self.layer_panel.opacity(layer);
+        let color   = |layer: EditorLayer| Color { a: opacity(layer), ..WHITE };
+        // When editing the background, the foreground is drawn over the top of it extra see-through
+        let draw_fg = |layer_bg: bool| {
+            let opacity = opacity(EditorLayer::Foreground) * if layer_bg { OTHER_LAYER_OPACITY } else { 1.0 };
+            editor_level.draw_fg(camera_pos, opacity, resources);
         };
 
-        // Draw the level
-        editor_level.draw_bg(camera_pos, self.layer_bg, resources);
-        if !self.layer_bg { draw_fg(false) }
+        if visible(EditorLayer::Background) {
+            editor_level.draw_bg(camera_pos, self.layer_bg, opacity(EditorLayer::Background), resources);
+        }
+        if !self.layer_bg && visible(EditorLayer::Foreground) { draw_fg(false) }
 
         // Render the signs
-        for (p, _) in editor_level.signs() {
-            Level::render_sign(*p, camera_pos, resources);
+        if visible(EditorLayer::Signs) {
+            for (p, _) in editor_level.signs() {
+                Level::render_sign(*p, camera_pos, color(EditorLayer::Signs), resources);
+            }
         }
         // Render the entities
-        for (pos, kind) in editor_level.entities() {
-            kind.draw_editor(false, true, *pos, camera_pos, resources);
+        if visible(EditorLayer::Entities) {
+            for (pos, kind, _) in editor_level.entities() {
+                kind.draw_editor(opacity(EditorLayer::Entities), true, *pos, camera_pos, resources);
+            }
         }
         // Render the doors and door start position
-        for (teleporter, pos, dest) in editor_level.doors() {
-            Level::render_door_debug(*teleporter, *pos, *dest, camera_pos, resources);
+        if visible(EditorLayer::Doors) {
+            for (teleporter, pos, dest) in editor_level.doors() {
+                Level::render_door_debug(*teleporter, *pos, *dest, camera_pos, color(EditorLayer::Doors), resources);
+            }
         }
         if let Object::Other(ObjectOtherKind::Door(kind)) = self.selected_object {
             if let Some(pos) = editor_level.door_start() {
@@ -527,11 +727,27 @@ impl LevelView {
             }
         }
         // Render checkpoints and spawn/finish
-        for c in editor_level.checkpoints() {
-            resources.draw_rect(*c - camera_pos, Rect::new(224.0, 16.0, 16.0, 16.0), false, false, WHITE, resources.entity_atlas());
+        if visible(EditorLayer::Checkpoints) {
+            for c in editor_level.checkpoints() {
+                resources.draw_rect(*c - camera_pos, Rect::new(224.0, 16.0, 16.0, 16.0), false, false, color(EditorLayer::Checkpoints), resources.entity_atlas());
+            }
         }
         Level::render_spawn_finish_debug(editor_level.spawn(), editor_level.finish(), camera_pos, resources);
 
+        self.jump_reach.draw(camera_pos, resources);
+        if self.death_heatmap {
+            draw_death_heatmap(deaths, editor_level, camera_pos, self.cursor_pos, resources);
+        }
+
+        // Draw the selected area
+        if let Some(rect) = self.selection_rect() {
+            let color = match resources.tile_animation_timer() % 0.4 > 0.2 {
+                true  => WHITE,
+                false => PURPLE,
+            };
+            draw_rectangle_lines(rect.x - camera_pos.x, rect.y - camera_pos.y, rect.w, rect.h, 1.0, color);
+        }
+
         // Draw the tile/entity the player is adding
         if let Some(pos) = self.cursor_pos {
             let draw_outline = |size: Vec2, color: Color| {
@@ -560,12 +776,12 @@ impl LevelView {
             else if let Object::Tile(tile) = self.selected_object {
                 if resources.tile_data(tile).texture().is_some() {
                     draw_outline(vec2(16.0, 16.0), WHITE);
-                    render_tile(&TileRenderData { tile, draw_kind: TileDrawKind::Single(0), pos}, camera_pos, TileRenderLayer::Foreground(false), resources);
+                    render_tile(&TileRenderData { tile, draw_kind: TileDrawKind::Single(0), pos}, camera_pos, TileRenderLayer::Foreground(1.0), resources);
                 }
             }
             else if let Object::Entity(entity_kind) = self.selected_object {
                 draw_outline(vec2(16.0, 16.0), WHITE);
-                entity_kind.draw_editor(true, true, pos, camera_pos, resources);
+                entity_kind.draw_editor(0.5, true, pos, camera_pos, resources);
             }
             else if let Object::Other(other) = self.selected_object {
                 let outline_col = match other {
@@ -582,16 +798,47 @@ impl LevelView {
             }
         }
 
-        if self.layer_bg { draw_fg(true) }
+        if self.layer_bg && visible(EditorLayer::Foreground) { draw_fg(true) }
+
+        // Color the tiles by their collision
+        if debug {
+            Level::render_collision_debug(editor_level.tiles(), editor_level.width(), camera_pos, resources);
+        }
 
         self.layer_switch_button.draw(resources);
+        self.layer_panel.draw(resources);
+        self.playtest_panel.draw(resources);
 
         if self.object_selector.active() {
             self.object_selector.draw(resources);
         }
 
+        // The tile inspector shows the tile being hovered in the object selector, or the one under the cursor,
+        // or the selected tile if there isn't one there
+        if self.tile_inspector && self.sign_popup.is_none() && self.entity_inspector.is_none() {
+            let tile = match self.object_selector.active() {
+                true  => match self.object_selector.hovered_object() {
+                    Some(Object::Tile(t)) => Some(t),
+                    _ => None,
+                },
+                false => self.cursor_pos
+                    .and_then(|p| editor_level.get_tile_at_pos(p, self.layer_bg))
+                    .filter(|t| *t != Tile::Empty)
+                    .or(match self.selected_object {
+                        Object::Tile(t) => Some(t),
+                        _ => None,
+                    }),
+            };
+            if let Some(tile) = tile {
+                draw_tile_inspector(tile, Ui::mouse_pos(), resources);
+            }
+        }
+
         if let Some(s) = &self.sign_popup {
             s.draw(resources);
         }
+        if let Some(e) = &self.entity_inspector {
+            e.draw(resources);
+        }
     }
 }
\ No newline at end of file
@@ -197,6 +197,21 @@ impl ObjectSelector {
         self.active = active;
     }
 
+    // The name shown when hovering over an object
+    pub fn object_name(&self, object: Object) -> Option<&String> {
+        self.object_buttons
+            .iter()
+            .find(|(o, _)| *o == object)
+            .and_then(|(_, b)| b.tooltip())
+    }
+
+    pub fn hovered_object(&self) -> Option<Object> {
+        self.object_buttons
+            .iter()
+            .find(|(_, b)| b.state() != ButtonState::Idle)
+            .map(|(o, _)| *o)
+    }
+
     pub fn update(&mut self, ui: &mut Ui) -> Option<Object> {
         for (o, b) in &mut self.object_buttons {
             b.update(ui);
@@ -224,10 +239,10 @@ impl ObjectSelector {
                 Object::Tile(t) => render_tile(
                     &TileRenderData { tile: *t, draw_kind: TileDrawKind::Single(0), pos},
                     Vec2::ZERO,
-                    TileRenderLayer::Foreground(false),
+                    TileRenderLayer::Foreground(1.0),
                     resources,
                 ),
-                Object::Entity(e) => e.draw_editor(false, false, pos, vec2(0.0, 0.0), resources),
+                Object::Entity(e) => e.draw_editor(1.0, false, pos, vec2(0.0, 0.0), resources),
                 Object::Other(o) => ObjectSelector::draw_object_other(pos, *o, false, resources),
             }
         }
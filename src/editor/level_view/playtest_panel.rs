@@ -0,0 +1,195 @@
+// A little panel for choosing what the player starts with when playtesting,
+// so the back half of a level can be tested without having to play through the front
+
+use macroquad::{color::{BLACK, GRAY, WHITE}, input::{is_key_down, KeyCode}, math::{vec2, Rect, Vec2}};
+
+use crate::{game::{level::tile::LockColor, player::{FeetPowerup, HeadPowerup, PowerupKind}}, resources::Resources, text_renderer::{render_text, Align, Font}, ui::{button::Button, Ui}, util::{draw_rect, draw_rect_lines}};
+
+const PANEL_RECT: Rect = Rect { x: 183.0, y: 18.0, w: 168.0, h: 100.0 };
+const MAX_COUNT: usize = 99;
+
+#[derive(Clone, Debug, Default)]
+pub struct PlaytestOptions {
+    pub head_powerup: Option<HeadPowerup>,
+    pub feet_powerup: Option<FeetPowerup>,
+    pub lives: usize,
+    pub chips: usize,
+    // Indexed the same as LockColor::colors()
+    pub locks_destroyed: [bool; 7],
+    // None means the switch blocks are left as they were placed
+    pub switch_state: Option<bool>,
+}
+
+impl PlaytestOptions {
+    pub fn locks_destroyed(&self) -> impl Iterator<Item = LockColor> + '_ {
+        LockColor::colors().iter().zip(self.locks_destroyed).filter(|(_, d)| *d).map(|(c, _)| *c)
+    }
+}
+
+pub struct PlaytestPanel {
+    active: bool,
+    options: PlaytestOptions,
+
+    open_button: Button,
+    head_button: Button,
+    feet_button: Button,
+    // The (-, +) buttons for lives and chips
+    lives_buttons: (Button, Button),
+    chips_buttons: (Button, Button),
+    lock_buttons: Vec<Button>,
+    switch_button: Button,
+}
+
+impl Default for PlaytestPanel {
+    fn default() -> Self {
+        let row_y = |i: usize| PANEL_RECT.y + 14.0 + i as f32 * 14.0;
+        let wide_button = |i: usize, tooltip: &str| {
+            Button::new(Rect::new(PANEL_RECT.x + 56.0, row_y(i), 108.0, 12.0), Some(String::new()), Some(tooltip.to_owned()))
+        };
+        let step_buttons = |i: usize| (
+            Button::new(Rect::new(PANEL_RECT.x +  56.0, row_y(i), 12.0, 12.0), Some(String::from("-")), None),
+            Button::new(Rect::new(PANEL_RECT.x + 152.0, row_y(i), 12.0, 12.0), Some(String::from("+")), None),
+        );
+
+        let mut panel = Self {
+            active: false,
+            options: PlaytestOptions::default(),
+
+            open_button: Button::new(Rect::new(303.0, 4.0, 48.0, 12.0), Some("Test".to_owned()), Some("Playtest options".to_owned())),
+            head_button: wide_button(0, "Head powerup"),
+            feet_button: wide_button(1, "Feet powerup"),
+            lives_buttons: step_buttons(2),
+            chips_buttons: step_buttons(3),
+            lock_buttons: (0..LockColor::colors().len())
+                .map(|i| Button::new(Rect::new(PANEL_RECT.x + 56.0 + i as f32 * 15.0, row_y(4), 12.0, 12.0), None, Some("Destroyed at start".to_owned())))
+                .collect(),
+            switch_button: wide_button(5, "Switch block state"),
+        };
+        panel.update_labels();
+        panel
+    }
+}
+
+impl PlaytestPanel {
+    pub fn options(&self) -> &PlaytestOptions {
+        &self.options
+    }
+
+    fn update_labels(&mut self) {
+        let powerup_name = |p: Option<PowerupKind>| p.map(|p| p.name().to_owned()).unwrap_or(String::from("None"));
+        self.head_button.set_label(powerup_name(self.options.head_powerup.map(PowerupKind::Head)));
+        self.feet_button.set_label(powerup_name(self.options.feet_powerup.map(PowerupKind::Feet)));
+        self.switch_button.set_label(match self.options.switch_state {
+            None        => "As placed",
+            Some(true)  => "On",
+            Some(false) => "Off",
+        });
+    }
+
+    pub fn update(&mut self, ui: &mut Ui) {
+        self.open_button.update(ui);
+        if self.open_button.released() {
+            self.active = !self.active;
+        }
+        if !self.active {
+            return;
+        }
+
+        // Holding shift changes the counts quicker
+        let amount = match is_key_down(KeyCode::LeftShift) {
+            true  => 10,
+            false => 1,
+        };
+        let options = &mut self.options;
+
+        self.head_button.update(ui);
+        if self.head_button.released() {
+            options.head_powerup = match options.head_powerup {
+                None => Some(HeadPowerup::Helmet),
+                Some(HeadPowerup::Helmet) => Some(HeadPowerup::XrayGoggles),
+                Some(HeadPowerup::XrayGoggles) => None,
+            };
+        }
+        self.feet_button.update(ui);
+        if self.feet_button.released() {
+            options.feet_powerup = match options.feet_powerup {
+                None => Some(FeetPowerup::Boots),
+                Some(FeetPowerup::Boots) => Some(FeetPowerup::MoonShoes),
+                Some(FeetPowerup::MoonShoes) => Some(FeetPowerup::Skirt),
+                Some(FeetPowerup::Skirt) => None,
+            };
+        }
+
+        for ((minus, plus), count) in [(&mut self.lives_buttons, &mut options.lives), (&mut self.chips_buttons, &mut options.chips)] {
+            minus.update(ui);
+            plus.update(ui);
+            if minus.released() {
+                *count = count.saturating_sub(amount);
+            }
+            if plus.released() {
+                *count = (*count + amount).min(MAX_COUNT);
+            }
+        }
+
+        for (button, destroyed) in self.lock_buttons.iter_mut().zip(options.locks_destroyed.iter_mut()) {
+            button.update(ui);
+            if button.released() {
+                *destroyed = !*destroyed;
+            }
+        }
+
+        self.switch_button.update(ui);
+        if self.switch_button.released() {
+            options.switch_state = match options.switch_state {
+                None        => Some(true),
+                Some(true)  => Some(false),
+                Some(false) => None,
+            };
+        }
+        self.update_labels();
+
+        // Stop the level from being edited through the panel
+        if Ui::mouse_pos().is_some_and(|m| PANEL_RECT.contains(m)) {
+            ui.interact();
+        }
+    }
+
+    pub fn draw(&self, resources: &Resources) {
+        self.open_button.draw(resources);
+        if !self.active {
+            return;
+        }
+
+        draw_rect(PANEL_RECT, GRAY);
+        draw_rect_lines(PANEL_RECT, BLACK);
+        render_text("Playtest with", WHITE, PANEL_RECT.point() + vec2(4.0, 3.0), Vec2::ONE, Align::End, Font::Small, resources);
+
+        let label = |text: &str, button: &Button| {
+            render_text(text, WHITE, vec2(PANEL_RECT.x + 4.0, button.rect().y + 2.0), Vec2::ONE, Align::End, Font::Small, resources);
+        };
+        label("Head",   &self.head_button);
+        label("Feet",   &self.feet_button);
+        label("Lives",  &self.lives_buttons.0);
+        label("Chips",  &self.chips_buttons.0);
+        label("Locks",  &self.lock_buttons[0]);
+        label("Switch", &self.switch_button);
+
+        self.head_button.draw(resources);
+        self.feet_button.draw(resources);
+        for ((minus, plus), count) in [(&self.lives_buttons, self.options.lives), (&self.chips_buttons, self.options.chips)] {
+            minus.draw(resources);
+            plus.draw(resources);
+            render_text(&format!("{count}"), WHITE, vec2(PANEL_RECT.x + 110.0, minus.rect().y + 6.0), Vec2::ONE, Align::Mid, Font::Small, resources);
+        }
+        // Destroyed locks are filled in, the rest are just outlined
+        for ((button, color), destroyed) in self.lock_buttons.iter().zip(LockColor::colors()).zip(self.options.locks_destroyed) {
+            button.draw(resources);
+            let swatch = Rect::new(button.rect().x + 3.0, button.rect().y + 3.0, 6.0, 6.0);
+            match destroyed {
+                true  => draw_rect(swatch, color.color(resources)),
+                false => draw_rect_lines(swatch, color.color(resources)),
+            }
+        }
+        self.switch_button.draw(resources);
+    }
+}
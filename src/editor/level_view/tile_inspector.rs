@@ -0,0 +1,72 @@
+// A little panel showing everything about a tile - how it collides, what happens when it's hit, and how it's drawn
+
+use macroquad::{color::{BLACK, GRAY, WHITE}, math::{vec2, Rect, Vec2}};
+
+use crate::{game::level::tile::{Tile, TileCollision, TileTextureConnection, TileTextureConnectionKind, TileTextureRenderType}, resources::Resources, text_renderer::{render_text, Align, Font}, util::{draw_rect, draw_rect_lines}, VIEW_SIZE};
+
+const WIDTH: f32 = 172.0;
+
+fn lines(tile: Tile, resources: &Resources) -> Vec<String> {
+    let data = resources.tile_data(tile);
+    let mut lines = vec![data.name().clone()];
+
+    let collision = data.collision();
+    lines.push(collision.name().to_owned());
+    if let TileCollision::Solid { hit_soft, hit_hard } = collision {
+        lines.push(format!("Soft hit: {}", hit_soft.name()));
+        lines.push(format!("Hard hit: {}", hit_hard.name()));
+    }
+
+    let Some(texture) = data.texture() else {
+        lines.push(String::from("No texture"));
+        return lines;
+    };
+    let connection_kind = match &texture.connection {
+        TileTextureConnection::None => {
+            lines.push(String::from("Doesn't connect"));
+            None
+        }
+        TileTextureConnection::Horizontal(k) => { lines.push(String::from("Connects: horiz")); Some(k) }
+        TileTextureConnection::Vertical(k)   => { lines.push(String::from("Connects: vert"));  Some(k) }
+        TileTextureConnection::Both(k)       => { lines.push(String::from("Connects: both"));  Some(k) }
+    };
+    match connection_kind {
+        Some(TileTextureConnectionKind::None) => lines.push(String::from(" to the same tile")),
+        Some(TileTextureConnectionKind::AllBut(t)) => lines.push(format!(" to all but {} tiles", t.len())),
+        Some(TileTextureConnectionKind::Only(t))   => lines.push(format!(" to only {} tiles", t.len())),
+        None => {}
+    }
+    match texture.render {
+        TileTextureRenderType::Fixed(_) => lines.push(String::from("Not animated")),
+        TileTextureRenderType::Animated { frames, frame_duration } => {
+            lines.push(format!("{} frames, {:.2}s", frames.len(), frame_duration));
+        }
+    }
+    if texture.above {
+        lines.push(String::from("Drawn above player"));
+    }
+    lines
+}
+
+// Drawn on the other side of the screen to the mouse so it doesn't get in the way
+pub fn draw_tile_inspector(tile: Tile, mouse_pos: Option<Vec2>, resources: &Resources) {
+    let lines = lines(tile, resources);
+    let h = 5.0 + lines.len() as f32 * 10.0;
+
+    let mouse_pos = mouse_pos.unwrap_or(Vec2::ZERO);
+    let x = match mouse_pos.x < VIEW_SIZE.x / 2.0 {
+        true  => VIEW_SIZE.x - WIDTH - 4.0,
+        false => 4.0,
+    };
+    let y = match mouse_pos.y < VIEW_SIZE.y / 2.0 {
+        true  => VIEW_SIZE.y - h - 16.0,
+        false => 4.0,
+    };
+    let rect = Rect::new(x, y, WIDTH, h);
+    draw_rect(rect, GRAY);
+    draw_rect_lines(rect, BLACK);
+
+    for (i, line) in lines.iter().enumerate() {
+        render_text(line, WHITE, rect.point() + vec2(4.0, 3.0 + i as f32 * 10.0), Vec2::ONE, Align::End, Font::Small, resources);
+    }
+}
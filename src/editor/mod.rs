@@ -4,12 +4,15 @@ use editor_menu::EditorMenu;
 use level_view::LevelView;
 use macroquad::{color::Color, input::{is_key_pressed, KeyCode}, math::vec2};
 
-use crate::{game::scene::Scene, level_pack_data::LevelPackData, resources::Resources, text_renderer::{render_text, Align, Font}, ui::{toast::ToastManager, Ui}, GameState};
+use crate::{game::{death_map::DeathMap, events::GameEvent, scene::{Scene, KEY_QUICKLOAD, KEY_QUICKSAVE}}, level_pack_data::LevelPackData, resources::Resources, text_renderer::{render_text, Align, Font}, ui::{toast::{ToastKind, ToastManager}, Ui}, GameState};
 
 pub mod editor_level;
 pub mod editor_level_pack;
 pub mod editor_menu;
 pub mod level_view;
+pub mod pixel_map;
+pub mod recovery;
+pub mod tiled;
 
 pub struct Editor {
     scene: Option<Scene>,
@@ -17,12 +20,16 @@ pub struct Editor {
     editor_level_pack: EditorLevelPack,
     editor_menu: EditorMenu,
     level_view: LevelView,
-    toast_manager: ToastManager,
+    autosave_timer: f32,
+    // Where the player has died in each level of the pack, shown on the heatmap
+    death_map: DeathMap,
     
     // I only need this so chips/lives update when running the scene.............
     chips: usize,
     lives: usize,
     instarun: bool,
+    // The scene, chips and lives when F5 was pressed during a playtest
+    quicksave: Option<(Scene, usize, usize)>,
 }
 
 impl Editor {
@@ -33,17 +40,42 @@ impl Editor {
         };
         editor_level_pack.editor_level_mut().update_if_should(resources);
 
+        // If there's an autosave from a session that wasn't saved, offer to restore it
+        let mut editor_menu = EditorMenu::new(editor_level_pack.file_name().clone());
+        if let Some(recovered) = recovery::read_recovery_file() {
+            editor_menu.open_recovery_popup(recovered);
+        }
+
+        let death_map = DeathMap::load(editor_level_pack.file_name());
         Self {
             scene: None,
             close_scene: false,
-            editor_menu: EditorMenu::new(editor_level_pack.file_name().clone()),
+            editor_menu,
             editor_level_pack,
             level_view: LevelView::new(resources),
-            toast_manager: ToastManager::default(),
+            autosave_timer: 0.0,
+            death_map,
 
             chips: 0,
             lives: 0,
             instarun: false,
+            quicksave: None,
+        }
+    }
+
+    // Every so often, write the pack to the recovery file if it has unsaved changes
+    fn update_autosave(&mut self, deltatime: f32, toast_manager: &mut ToastManager) {
+        self.autosave_timer += deltatime;
+        if self.autosave_timer < recovery::AUTOSAVE_INTERVAL {
+            return;
+        }
+        self.autosave_timer = 0.0;
+        if !self.editor_level_pack.dirty() {
+            return;
+        }
+        if let Err(e) = recovery::write_recovery_file(&self.editor_level_pack) {
+            toast_manager.add_toast(String::from("Couldn't autosave?!"), ToastKind::Error);
+            toast_manager.add_toast(format!("{e}"), ToastKind::Error);
         }
     }
 
@@ -53,22 +85,26 @@ impl Editor {
 }
 
 impl GameState for Editor {
-    fn update(&mut self, deltatime: f32, ui: &mut Ui, resources: &mut Resources, next_state: &mut Option<Box<dyn GameState>>) {
+    fn update(&mut self, deltatime: f32, ui: &mut Ui, resources: &mut Resources, toast_manager: &mut ToastManager, next_state: &mut Option<Box<dyn GameState>>) {
+        self.update_autosave(deltatime, toast_manager);
+
         // for testing
         if self.instarun {
             self.instarun = false;
-            self.scene = Some(Scene::from_editor_level(&self.editor_level_pack.editor_level(), None));
-            self.chips = 0;
-            self.lives = 0;
+            let options = self.level_view.playtest_options();
+            self.scene = Some(Scene::from_editor_level(&self.editor_level_pack.editor_level(), None, options));
+            self.chips = options.chips;
+            self.lives = options.lives;
             resources.reset_tile_animation_timer();
         }
 
         // If the test spawn point has been placed, run the scene from there
         if let Some((pos, place)) = self.level_view.test_spawn_point() {
             if place {
-                self.scene = Some(Scene::from_editor_level(&self.editor_level_pack.editor_level(), Some(pos)));
-                self.lives = 0;
-                self.chips = 0;
+                let options = self.level_view.playtest_options();
+                self.scene = Some(Scene::from_editor_level(&self.editor_level_pack.editor_level(), Some(pos), options));
+                self.lives = options.lives;
+                self.chips = options.chips;
                 resources.reset_tile_animation_timer();
                 self.level_view.clear_test_spawn_point();
             }
@@ -77,9 +113,53 @@ impl GameState for Editor {
         if self.close_scene || self.scene.as_ref().is_some_and(|s| s.completed()) {
             self.close_scene = false;
             self.scene = None;
+            self.quicksave = None;
+        }
+        // Saving the pack can move levels around in the file, so their deaths need to move with them
+        // If it was saved under a different name, the deaths go along to the new name too
+        if let Some(old_indices) = self.editor_level_pack.take_saved_remap() {
+            self.death_map.remap(&old_indices);
+            self.death_map.set_pack_file_name(self.editor_level_pack.file_name().clone());
+            if self.death_map.save().is_err() {
+                toast_manager.add_toast(String::from("Couldn't save deaths!"), ToastKind::Warning);
+            }
+        }
+        // Otherwise a different pack was opened
+        else if self.death_map.pack_file_name() != self.editor_level_pack.file_name() {
+            self.death_map = DeathMap::load(self.editor_level_pack.file_name());
         }
         if let Some(scene) = &mut self.scene {
-            scene.update(&mut self.chips, &mut self.lives, deltatime, resources);
+            scene.update(deltatime, resources);
+            for event in scene.take_events() {
+                match event {
+                    GameEvent::ChipCollected => self.chips += 1,
+                    GameEvent::LifeCollected => self.lives += 1,
+                    _ => {}
+                }
+            }
+            // Levels that haven't been saved yet don't have anywhere in the file to keep their deaths
+            if let Some((level, death)) = self.editor_level_pack.saved_index().zip(scene.take_death()) {
+                self.death_map.record(level, death);
+                if self.death_map.save().is_err() {
+                    toast_manager.add_toast(String::from("Couldn't save deaths!"), ToastKind::Warning);
+                }
+            }
+            // Quicksaving / quickloading the whole scene
+            if is_key_pressed(KEY_QUICKSAVE) {
+                self.quicksave = Some((scene.clone(), self.chips, self.lives));
+                toast_manager.add_toast(String::from("Quicksaved"), ToastKind::Info);
+            }
+            if is_key_pressed(KEY_QUICKLOAD) {
+                match &self.quicksave {
+                    Some((saved, chips, lives)) => {
+                        *scene = saved.clone();
+                        self.chips = *chips;
+                        self.lives = *lives;
+                        toast_manager.add_toast(String::from("Quickloaded"), ToastKind::Info);
+                    }
+                    None => toast_manager.add_toast(String::from("No quicksave! (F5)"), ToastKind::Warning),
+                }
+            }
             // If we're in the scene and tab or esc is pressed, exit on the next frame
             // We do this so scene isn't None when drawing it this frame
             if is_key_pressed(KeyCode::Tab) || is_key_pressed(KeyCode::Escape) {
@@ -93,22 +173,35 @@ impl GameState for Editor {
                 self.level_view.close_object_selector();
             } else if self.level_view.sign_popup_open() {
                 self.level_view.close_sign_popup();
+            } else if self.level_view.entity_inspector_open() {
+                self.level_view.close_entity_inspector();
             } else {
                 self.editor_menu.set_active(!self.editor_menu.active());
                 self.level_view.clear_cursor();
             }
         }
 
-        // Update the toasts
-        self.toast_manager.update(deltatime);
-        
         // If the menu is open, update that and don't update the view
         if self.editor_menu.active() {
-            self.editor_menu.update(next_state, &mut self.editor_level_pack, &mut self.level_view, &mut self.toast_manager, deltatime, ui, &resources);
+            self.editor_menu.update(next_state, &mut self.editor_level_pack, &mut self.level_view, toast_manager, deltatime, ui, &resources);
             return;
         }
 
-        self.level_view.update(self.editor_level_pack.editor_level_mut(), &mut self.editor_menu, &mut self.toast_manager, deltatime, ui, resources);
+        self.level_view.update(self.editor_level_pack.editor_level_mut(), &mut self.editor_menu, toast_manager, deltatime, ui, resources);
+
+        if self.level_view.take_clear_deaths() {
+            // Levels that haven't been saved don't have any deaths to clear
+            match self.editor_level_pack.saved_index() {
+                None => toast_manager.add_toast(String::from("Level isn't saved, no deaths to clear!"), ToastKind::Warning),
+                Some(level) => {
+                    self.death_map.clear_level(level);
+                    match self.death_map.save() {
+                        Ok(_)  => toast_manager.add_toast(String::from("Cleared deaths in level"), ToastKind::Success),
+                        Err(_) => toast_manager.add_toast(String::from("Couldn't save deaths!"), ToastKind::Warning),
+                    }
+                }
+            }
+        }
     }
 
     fn draw(&self, _ui: &Ui, resources: &Resources, debug: bool) {
@@ -118,14 +211,13 @@ impl GameState for Editor {
             return;
         }
 
-        self.level_view.draw(self.editor_level_pack.editor_level(), resources);
+        let deaths = self.editor_level_pack.saved_index().map(|i| self.death_map.deaths(i)).unwrap_or(&[]);
+        self.level_view.draw(self.editor_level_pack.editor_level(), deaths, resources, debug);
 
         if self.editor_menu.active() {
             self.editor_menu.draw(&self.editor_level_pack, resources);
         }
 
-        self.toast_manager.draw(resources);
-
         Editor::draw_editor_logo(resources);
     }
 }
\ No newline at end of file
@@ -0,0 +1,135 @@
+// Making a level from a png, where each pixel is a tile
+// This lets levels be sketched out in an image editor and then touched up in here
+//
+// The colours used for each tile are in a mapping file (made with some defaults if it doesn't exist), with lines like:
+// RRGGBB <tile id>
+// Some colours are reserved though:
+// - transparent / FFFFFF: empty
+// - 00FF00: spawn
+// - FF0000: finish
+// - 0000FF: checkpoint
+// - FF00xx: entity, where xx is 80 + the entity id (in hex)
+
+use std::collections::HashMap;
+
+use macroquad::{math::vec2, prelude::ImageFormat, texture::Image};
+
+use crate::game::{entity::{props::EntityProps, EntityKind}, level::tile::Tile};
+
+use super::editor_level::{EditorLevel, BG_SKY, MAX_CHECKPOINTS, MAX_ENTITIES, MAX_HEIGHT, MAX_WIDTH, MIN_HEIGHT, MIN_WIDTH};
+
+const MAPPING_FILE: &str = "pixel_map.txt";
+
+type Rgb = (u8, u8, u8);
+
+const EMPTY:      Rgb = (255, 255, 255);
+const SPAWN:      Rgb = (  0, 255,   0);
+const FINISH:     Rgb = (255,   0,   0);
+const CHECKPOINT: Rgb = (  0,   0, 255);
+// Entities are (255, 0, ENTITY_BASE + id)
+const ENTITY_BASE: u8 = 0x80;
+
+const DEFAULT_MAPPING: &str = "\
+# Colours for making levels from pngs, each line is: RRGGBB <tile id>
+# Tile ids are the same as the ones in .fox files
+# Reserved colours (these can't be used for tiles):
+#  transparent / FFFFFF = empty
+#  00FF00 = spawn, FF0000 = finish, 0000FF = checkpoint
+#  FF00xx = entity, where xx is 80 + the entity id (in hex)
+000000 4
+808080 6
+8B4513 3
+228B22 2
+";
+
+fn reserved(color: Rgb) -> bool {
+    matches!(color, EMPTY | SPAWN | FINISH | CHECKPOINT) || (color.0 == 255 && color.1 == 0 && color.2 >= ENTITY_BASE)
+}
+
+// Reads the mapping file, writing the default one first if there isn't one so it can be changed
+fn read_mapping() -> Result<HashMap<Rgb, Tile>, String> {
+    let text = match std::fs::read_to_string(MAPPING_FILE) {
+        Ok(t) => t,
+        Err(_) => {
+            let _ = std::fs::write(MAPPING_FILE, DEFAULT_MAPPING);
+            DEFAULT_MAPPING.to_owned()
+        }
+    };
+
+    let mut mapping = HashMap::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let bad_line = || format!("Mapping line {} is bad", i + 1);
+        let (color, id) = line.split_once(char::is_whitespace).ok_or_else(bad_line)?;
+        let color = u32::from_str_radix(color, 16).ok().filter(|_| color.len() == 6).ok_or_else(bad_line)?;
+        let color = ((color >> 16) as u8, (color >> 8) as u8, color as u8);
+        let tile = id.trim().parse::<u8>().ok().and_then(|id| Tile::try_from(id).ok()).ok_or_else(bad_line)?;
+        if reserved(color) {
+            return Err(format!("Mapping line {} uses reserved colour", i + 1));
+        }
+        mapping.insert(color, tile);
+    }
+    Ok(mapping)
+}
+
+pub fn level_from_png(file_name: &str) -> Result<EditorLevel, String> {
+    let mapping = read_mapping()?;
+    let bytes = std::fs::read(format!("{file_name}.png")).map_err(|e| format!("{e}"))?;
+    let image = Image::from_file_with_format(&bytes, Some(ImageFormat::Png))
+        .map_err(|_| String::from("Couldn't read png!"))?;
+
+    // Anything past the max size is cut off, and small images are put in the bottom left of the level
+    let (image_w, image_h) = (image.width().min(MAX_WIDTH), image.height().min(MAX_HEIGHT));
+    let (width, height) = (image_w.max(MIN_WIDTH), image_h.max(MIN_HEIGHT));
+    let offset_y = height - image_h;
+
+    let mut tiles = vec![Tile::Empty; width * height];
+    let mut spawn = None;
+    let mut finish = None;
+    let mut checkpoints = vec![];
+    let mut entities = vec![];
+    let mut unknown = 0;
+
+    let data = image.get_image_data();
+    for y in 0..image_h {
+        for x in 0..image_w {
+            let [r, g, b, a] = data[y * image.width() + x];
+            let color = (r, g, b);
+            let index = (y + offset_y) * width + x;
+            let pos = vec2(x as f32, (y + offset_y) as f32) * 16.0;
+
+            if a == 0 || color == EMPTY {
+                continue;
+            }
+            match color {
+                SPAWN      => spawn  = spawn.or(Some(pos)),
+                FINISH     => finish = finish.or(Some(pos)),
+                CHECKPOINT => if checkpoints.len() < MAX_CHECKPOINTS { checkpoints.push(pos) },
+                (255, 0, b) if b >= ENTITY_BASE => match EntityKind::try_from(b - ENTITY_BASE) {
+                    Ok(kind) if entities.len() < MAX_ENTITIES => entities.push((pos, kind, EntityProps::default())),
+                    _ => unknown += 1,
+                },
+                c => match mapping.get(&c) {
+                    Some(tile) => tiles[index] = *tile,
+                    None => unknown += 1,
+                },
+            }
+        }
+    }
+    let spawn  = spawn .ok_or_else(|| String::from("No spawn (00FF00) in png!"))?;
+    let finish = finish.ok_or_else(|| String::from("No finish (FF0000) in png!"))?;
+    if unknown > 0 {
+        return Err(format!("{unknown} pixels have unknown colours!"));
+    }
+
+    let mut level = EditorLevel::new(
+        String::new(), file_name.to_owned(), BG_SKY, width, height,
+        tiles, vec![Tile::Empty; width * height], vec![], vec![],
+        spawn, finish, checkpoints, entities,
+    );
+    level.mark_modified();
+    Ok(level)
+}
@@ -0,0 +1,45 @@
+// Autosaving the pack being edited to a recovery file, so it isn't lost if the game crashes (or is closed without saving)
+
+use crate::level_pack_data::LevelPackData;
+
+use super::editor_level_pack::EditorLevelPack;
+
+// Not a .fox file so it doesn't show up in the menu's pack list
+const RECOVERY_FILE: &str = "editor_recovery.foxbak";
+// How often the pack is autosaved (in seconds) while it has unsaved changes
+pub const AUTOSAVE_INTERVAL: f32 = 30.0;
+
+pub fn write_recovery_file(editor_level_pack: &EditorLevelPack) -> std::io::Result<()> {
+    // The file name isn't part of the pack's bytes, so it's stored before them
+    let file_name = editor_level_pack.file_name();
+    let mut bytes = vec![file_name.len() as u8];
+    bytes.extend_from_slice(file_name.as_bytes());
+    // Same with where each level is in the saved pack (for the death heatmaps), 255 being not saved yet
+    let saved_indices = editor_level_pack.saved_indices();
+    bytes.push(saved_indices.len() as u8);
+    bytes.extend(saved_indices.iter().map(|i| i.map(|i| i as u8).unwrap_or(u8::MAX)));
+    bytes.extend(LevelPackData::from_editor_level_pack(editor_level_pack).to_bytes());
+
+    // Write to a temporary file first so crashing mid-write doesn't ruin the previous autosave
+    let temp = format!("{RECOVERY_FILE}.tmp");
+    std::fs::write(&temp, bytes)?;
+    std::fs::rename(temp, RECOVERY_FILE)
+}
+
+pub fn read_recovery_file() -> Option<EditorLevelPack> {
+    let bytes = std::fs::read(RECOVERY_FILE).ok()?;
+    let file_name_len = *bytes.first()? as usize;
+    let file_name = String::from_utf8(bytes.get(1..1+file_name_len)?.to_vec()).ok()?;
+    let bytes = bytes.get(1+file_name_len..)?;
+    let level_count = *bytes.first()? as usize;
+    let saved_indices = bytes.get(1..1+level_count)?.iter().map(|&i| (i != u8::MAX).then_some(i as usize)).collect();
+    let pack_data = LevelPackData::from_bytes(file_name, bytes.get(1+level_count..)?)?;
+    let mut pack = pack_data.to_editor_level_pack();
+    pack.set_saved_indices(saved_indices);
+    Some(pack)
+}
+
+pub fn remove_recovery_file() {
+    // If it doesn't exist then that's fine!
+    let _ = std::fs::remove_file(RECOVERY_FILE);
+}
@@ -0,0 +1,686 @@
+// Exporting a level to a Tiled map and importing it back, so big layouts can be done in Tiled
+// Both kinds of Tiled map are written, the json .tmj one and the xml .tmx one, and either can be imported.
+//
+// The map has two tile layers ('background' and 'foreground') and an object layer ('objects')
+// with the spawn, finish, checkpoints, entities, signs and doors in it
+// The tileset is made from res/tiles.png when exporting (see level_image::export_tileset), with every tile in the cell for its id.
+// This means the tile ids in the map are the same as the ones in .fox files, and they look right in Tiled too!
+//
+// The map is put together as json (the same as the .tmj), and the .tmx is written from that.
+// When importing a .tmx it's turned back into that json, so the rest of the importing is the same for both.
+// There's no json or xml crate in here, so there's a tiny reader/writer for each at the bottom of this file
+
+use macroquad::math::{vec2, Vec2};
+
+use crate::{game::{entity::{props::EntityProps, EntityKind}, level::{things::DoorKind, tile::Tile}}, level_image, level_pack_data::VERSION, ui::text_input::MAX_USER_STRING_LEN};
+
+use super::editor_level::{EditorLevel, MAX_CHECKPOINTS, MAX_DOORS, MAX_ENTITIES, MAX_HEIGHT, MAX_SIGNS, MAX_WIDTH, MIN_HEIGHT, MIN_WIDTH};
+
+// Written next to the maps, as Tiled looks for tilesets relative to the map
+const TILESET: &str = "tiled_tiles.png";
+const TILESET_SIZE: usize = 16 * 16;
+// The names of the entity props in the object properties, in the same order as EntityProps::to_bytes
+const PROP_NAMES: [&str; 7] = ["facing", "phase", "period", "health", "vel_x", "vel_y", "group"];
+// Tiled keeps flipping flags in the top bits of each tile
+const GID_MASK: u32 = 0x0FFFFFFF;
+
+// An object in the object layer, as (type, position, properties)
+type Object<'a> = (&'a str, Vec2, Vec<(&'a str, Json)>);
+
+fn num(n: impl Into<f64>) -> Json {
+    Json::Num(n.into())
+}
+fn string(s: &str) -> Json {
+    Json::Str(s.to_owned())
+}
+fn object(fields: Vec<(&str, Json)>) -> Json {
+    Json::Obj(fields.into_iter().map(|(k, v)| (k.to_owned(), v)).collect())
+}
+
+// Writes the level to 'file_name.tmj' and 'file_name.tmx'
+pub fn export_level(editor_level: &EditorLevel, file_name: &str) -> Result<(), String> {
+    level_image::export_tileset(TILESET)?;
+    let map = map_json(editor_level);
+    std::fs::write(format!("{file_name}.tmj"), map.write()).map_err(|e| format!("{e}"))?;
+    std::fs::write(format!("{file_name}.tmx"), write_tmx(&map)).map_err(|e| format!("{e}"))
+}
+
+fn map_json(editor_level: &EditorLevel) -> Json {
+    let (w, h) = (editor_level.width() as u32, editor_level.height() as u32);
+
+    let tile_layer = |id: u32, name: &str, tiles: &[Tile]| object(vec![
+        ("type", string("tilelayer")), ("id", num(id)), ("name", string(name)),
+        ("x", num(0)), ("y", num(0)), ("width", num(w)), ("height", num(h)), ("opacity", num(1)), ("visible", Json::Bool(true)),
+        ("data", Json::Arr(tiles.iter().map(|t| num(u8::from(*t))).collect())),
+    ]);
+
+    let mut objects: Vec<Object> = vec![
+        ("spawn",  editor_level.spawn(),  vec![]),
+        ("finish", editor_level.finish(), vec![]),
+    ];
+    for c in editor_level.checkpoints() {
+        objects.push(("checkpoint", *c, vec![]));
+    }
+    for (pos, kind, props) in editor_level.entities() {
+        let mut properties = vec![("kind", num(u8::from(*kind)))];
+        for (name, byte) in PROP_NAMES.iter().zip(props.to_bytes()) {
+            // Velocities can be negative
+            let value = match name.starts_with("vel") {
+                true  => num(byte as i8),
+                false => num(byte),
+            };
+            properties.push((name, value));
+        }
+        objects.push(("entity", *pos, properties));
+    }
+    for (pos, lines) in editor_level.signs() {
+        let properties = ["line1", "line2", "line3", "line4"].into_iter().zip(lines).map(|(n, l)| (n, Json::Str(l.clone()))).collect();
+        objects.push(("sign", *pos, properties));
+    }
+    for (kind, pos, dest) in editor_level.doors() {
+        objects.push(("door", *pos, vec![
+            ("kind",   num(u8::from(*kind))),
+            ("dest_x", num(dest.x)),
+            ("dest_y", num(dest.y)),
+        ]));
+    }
+    let next_object_id = objects.len() as u32 + 1;
+    let objects = objects.into_iter().enumerate().map(|(i, (kind, pos, properties))| object(vec![
+        ("id", num(i as u32 + 1)), ("name", string("")), ("type", string(kind)),
+        ("x", num(pos.x)), ("y", num(pos.y)), ("width", num(16)), ("height", num(16)), ("rotation", num(0)), ("visible", Json::Bool(true)),
+        ("properties", properties_json(properties)),
+    ])).collect();
+
+    let (r, g, b) = editor_level.bg_col();
+    object(vec![
+        ("type", string("map")), ("version", string("1.10")), ("orientation", string("orthogonal")), ("renderorder", string("right-down")), ("infinite", Json::Bool(false)),
+        ("width", num(w)), ("height", num(h)), ("tilewidth", num(16)), ("tileheight", num(16)), ("backgroundcolor", Json::Str(format!("#{r:02x}{g:02x}{b:02x}"))),
+        ("nextlayerid", num(4)), ("nextobjectid", num(next_object_id)),
+        ("properties", properties_json(vec![("name", Json::Str(editor_level.name().clone())), ("world", Json::Str(editor_level.world().clone()))])),
+        ("tilesets", Json::Arr(vec![object(vec![
+            ("firstgid", num(1)), ("name", string("tiles")), ("image", string(TILESET)), ("imagewidth", num(TILESET_SIZE as u32)), ("imageheight", num(TILESET_SIZE as u32)),
+            // Tile 0 is empty, so it doesn't get a cell
+            ("tilewidth", num(16)), ("tileheight", num(16)), ("columns", num(16)), ("tilecount", num(u8::MAX)), ("margin", num(0)), ("spacing", num(0)),
+        ])])),
+        ("layers", Json::Arr(vec![
+            tile_layer(1, "background", editor_level.tiles_bg()),
+            tile_layer(2, "foreground", editor_level.tiles()),
+            object(vec![
+                ("type", string("objectgroup")), ("id", num(3)), ("name", string("objects")), ("draworder", string("topdown")),
+                ("x", num(0)), ("y", num(0)), ("opacity", num(1)), ("visible", Json::Bool(true)),
+                ("objects", Json::Arr(objects)),
+            ]),
+        ])),
+    ])
+}
+
+fn properties_json(properties: Vec<(&str, Json)>) -> Json {
+    Json::Arr(properties.into_iter().map(|(name, value)| {
+        let kind = match value {
+            Json::Str(_) => "string",
+            _ => "int",
+        };
+        object(vec![("name", string(name)), ("type", string(kind)), ("value", value)])
+    }).collect())
+}
+
+fn json_arr<'a>(json: &'a Json, key: &str) -> &'a [Json] {
+    json.get(key).and_then(Json::as_arr).map(|a| a.as_slice()).unwrap_or(&[])
+}
+
+// The json map as xml, the plain values of each json object become the attributes of its element
+fn write_tmx(map: &Json) -> String {
+    let properties = |json: &Json| match json_arr(json, "properties") {
+        [] => String::new(),
+        p => format!("<properties>\n{}</properties>\n", p.iter().map(|p| tmx_element("property", p, &[], String::new())).collect::<String>()),
+    };
+
+    let mut children = properties(map);
+    for tileset in json_arr(map, "tilesets") {
+        let image = format!(
+            "<image source=\"{}\" width=\"{}\" height=\"{}\"/>\n",
+            xml_escape(tileset.get("image").and_then(Json::as_str).unwrap_or_default()),
+            tileset.get("imagewidth").and_then(Json::as_usize).unwrap_or_default(),
+            tileset.get("imageheight").and_then(Json::as_usize).unwrap_or_default(),
+        );
+        children += &tmx_element("tileset", tileset, &["image", "imagewidth", "imageheight"], image);
+    }
+    for layer in json_arr(map, "layers") {
+        children += &match layer.get("type").and_then(Json::as_str) {
+            Some("tilelayer") => {
+                // One row per line, like Tiled does
+                let width = layer.get("width").and_then(Json::as_usize).unwrap_or(1).max(1);
+                let data = json_arr(layer, "data")
+                    .chunks(width)
+                    .map(|row| row.iter().map(Json::write).collect::<Vec<_>>().join(","))
+                    .collect::<Vec<_>>()
+                    .join(",\n");
+                tmx_element("layer", layer, &["type"], format!("<data encoding=\"csv\">\n{data}\n</data>\n"))
+            }
+            Some("objectgroup") => {
+                let objects = json_arr(layer, "objects").iter().map(|o| tmx_element("object", o, &[], properties(o))).collect();
+                tmx_element("objectgroup", layer, &["type"], objects)
+            }
+            _ => String::new(),
+        };
+    }
+    format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{}", tmx_element("map", map, &["type"], children))
+}
+
+// Arrays and anything in 'skip' don't become attributes
+fn tmx_element(name: &str, json: &Json, skip: &[&str], children: String) -> String {
+    let Json::Obj(fields) = json else {
+        return String::new();
+    };
+    let attributes: String = fields.iter()
+        .filter(|(k, v)| !skip.contains(&k.as_str()) && !matches!(v, Json::Arr(_) | Json::Obj(_)))
+        .map(|(k, v)| {
+            let value = match v {
+                Json::Str(s) => xml_escape(s),
+                // Tiled uses 1/0 for bools in tmx files
+                Json::Bool(b) => (*b as u8).to_string(),
+                v => v.write(),
+            };
+            format!(" {k}=\"{value}\"")
+        })
+        .collect();
+    match children.is_empty() {
+        true  => format!("<{name}{attributes}/>\n"),
+        false => format!("<{name}{attributes}>\n{children}</{name}>\n"),
+    }
+}
+
+// Turns a .tmx map into json that looks like the .tmj version of it
+fn json_from_tmx(map: &Xml) -> Option<Json> {
+    if map.name != "map" {
+        return None;
+    }
+    // Attributes that look like numbers are numbers, everything else is a string
+    let attributes = |xml: &Xml, kind: Option<&str>| -> Vec<(String, Json)> {
+        let mut fields: Vec<(String, Json)> = xml.attributes.iter()
+            .map(|(k, v)| (k.clone(), v.parse().map(Json::Num).unwrap_or_else(|_| Json::Str(v.clone()))))
+            .collect();
+        if let Some(kind) = kind {
+            fields.push((String::from("type"), string(kind)));
+        }
+        // Property values are typed, so they're read separately
+        let properties = xml.child("properties").into_iter().flat_map(|p| p.children("property")).map(|p| {
+            let value = p.attribute("value").map(str::to_owned).unwrap_or_else(|| p.text.clone());
+            let value = match p.attribute("type") {
+                Some("int" | "float") => value.parse().map(Json::Num).unwrap_or(Json::Null),
+                Some("bool") => Json::Bool(value == "true"),
+                _ => Json::Str(value),
+            };
+            object(vec![("name", string(p.attribute("name").unwrap_or_default())), ("value", value)])
+        }).collect();
+        fields.push((String::from("properties"), Json::Arr(properties)));
+        fields
+    };
+
+    let mut layers = vec![];
+    for layer in &map.children {
+        match layer.name.as_str() {
+            "layer" => {
+                let mut fields = attributes(layer, Some("tilelayer"));
+                let data = layer.child("data")?;
+                let gids = match data.attribute("encoding") {
+                    Some("csv") => data.text.split(',').map(|gid| gid.trim().parse().ok().map(Json::Num)).collect::<Option<Vec<_>>>()?,
+                    // No encoding means each tile is its own element
+                    None => data.children("tile").map(|t| num(t.attribute("gid").and_then(|g| g.parse::<u32>().ok()).unwrap_or(0))).collect(),
+                    Some(encoding) => {
+                        fields.push((String::from("encoding"), string(encoding)));
+                        vec![]
+                    }
+                };
+                fields.push((String::from("data"), Json::Arr(gids)));
+                layers.push(Json::Obj(fields));
+            }
+            "objectgroup" => {
+                let mut fields = attributes(layer, Some("objectgroup"));
+                let objects = layer.children("object").map(|o| Json::Obj(attributes(o, None))).collect();
+                fields.push((String::from("objects"), Json::Arr(objects)));
+                layers.push(Json::Obj(fields));
+            }
+            _ => {}
+        }
+    }
+
+    let mut fields = attributes(map, Some("map"));
+    fields.push((String::from("tilesets"), Json::Arr(map.children("tileset").map(|t| Json::Obj(attributes(t, None))).collect())));
+    fields.push((String::from("layers"), Json::Arr(layers)));
+    Some(Json::Obj(fields))
+}
+
+// The extension can be typed in, otherwise it looks for a .tmj and then a .tmx
+pub fn import_level(file_name: &str) -> Result<EditorLevel, String> {
+    let path = match file_name.ends_with(".tmj") || file_name.ends_with(".tmx") {
+        true  => file_name.to_owned(),
+        false => [".tmj", ".tmx"].iter()
+            .map(|e| format!("{file_name}{e}"))
+            .find(|p| std::path::Path::new(p).exists())
+            .unwrap_or_else(|| format!("{file_name}.tmj")),
+    };
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("{e}"))?;
+    let map = match path.ends_with(".tmx") {
+        true  => Xml::parse(&text).and_then(|x| json_from_tmx(&x)).ok_or_else(|| String::from("Map isn't valid xml!"))?,
+        false => Json::parse(&text).ok_or_else(|| String::from("Map isn't valid json!"))?,
+    };
+    let bad_map = || String::from("Map is missing things!");
+
+    let width  = map.get("width") .and_then(Json::as_usize).ok_or_else(bad_map)?;
+    let height = map.get("height").and_then(Json::as_usize).ok_or_else(bad_map)?;
+    if !(MIN_WIDTH..=MAX_WIDTH).contains(&width) || !(MIN_HEIGHT..=MAX_HEIGHT).contains(&height) {
+        return Err(format!("Map must be {MIN_WIDTH}x{MIN_HEIGHT} to {MAX_WIDTH}x{MAX_HEIGHT}!"));
+    }
+    let first_gid = map.get("tilesets")
+        .and_then(|t| t.as_arr()?.first()?.get("firstgid")?.as_usize())
+        .unwrap_or(1) as u32;
+
+    let layers = map.get("layers").and_then(Json::as_arr).ok_or_else(bad_map)?;
+    let layer = |name: &str| layers.iter().find(|l| l.get("name").and_then(Json::as_str) == Some(name));
+    let tiles_from_layer = |name: &str| -> Result<Vec<Tile>, String> {
+        let Some(layer) = layer(name) else {
+            return Ok(vec![Tile::Empty; width * height]);
+        };
+        if layer.get("encoding").is_some_and(|e| e.as_str() != Some("csv")) {
+            return Err(String::from("Layer format must be CSV!"));
+        }
+        let data = layer.get("data").and_then(Json::as_arr).filter(|d| d.len() == width * height).ok_or_else(bad_map)?;
+        data.iter().map(|gid| {
+            let gid = gid.as_usize().ok_or_else(bad_map)? as u32 & GID_MASK;
+            if gid == 0 {
+                return Ok(Tile::Empty);
+            }
+            (gid + 1).checked_sub(first_gid)
+                .and_then(|id| u8::try_from(id).ok())
+                .and_then(|id| Tile::try_from(id).ok())
+                .ok_or_else(|| format!("Unknown tile {gid}!"))
+        }).collect()
+    };
+    let tiles    = tiles_from_layer("foreground")?;
+    let tiles_bg = tiles_from_layer("background")?;
+
+    let level_size = vec2(width as f32, height as f32) * 16.0;
+    let mut spawn = None;
+    let mut finish = None;
+    let mut checkpoints = vec![];
+    let mut entities = vec![];
+    let mut signs = vec![];
+    let mut doors = vec![];
+
+    let objects = layer("objects").and_then(|l| l.get("objects")?.as_arr()).map(|o| o.as_slice()).unwrap_or(&[]);
+    for object in objects {
+        // Everything is snapped to the grid, and has to be in the level
+        let pos = |x: &str, y: &str| -> Option<Vec2> {
+            let pos = vec2(object.get(x)?.as_f64()? as f32, object.get(y)?.as_f64()? as f32);
+            let pos = (pos / 16.0).floor() * 16.0;
+            (pos.x >= 0.0 && pos.y >= 0.0 && pos.x < level_size.x && pos.y < level_size.y).then_some(pos)
+        };
+        let property = |name: &str| -> Option<&Json> {
+            object.get("properties")?.as_arr()?.iter().find(|p| p.get("name").and_then(Json::as_str) == Some(name))?.get("value")
+        };
+        let property_pos = |x: &str, y: &str| -> Option<Vec2> {
+            let pos = vec2(property(x)?.as_f64()? as f32, property(y)?.as_f64()? as f32);
+            Some((pos / 16.0).floor() * 16.0)
+        };
+        // Newer versions of Tiled call the type 'class'
+        let kind = object.get("type").or(object.get("class")).and_then(Json::as_str);
+        let Some(pos) = pos("x", "y") else {
+            continue;
+        };
+
+        match kind {
+            Some("spawn")  => spawn  = Some(pos),
+            Some("finish") => finish = Some(pos),
+            Some("checkpoint") if checkpoints.len() < MAX_CHECKPOINTS => checkpoints.push(pos),
+            Some("entity") if entities.len() < MAX_ENTITIES => {
+                let Some(kind) = property("kind").and_then(Json::as_usize).and_then(|k| EntityKind::try_from(k as u8).ok()) else {
+                    continue;
+                };
+                let bytes = PROP_NAMES.map(|n| property(n).and_then(Json::as_f64).unwrap_or(0.0) as i64 as u8);
+                entities.push((pos, kind, EntityProps::from_bytes(&bytes, VERSION).unwrap_or_default()));
+            }
+            Some("sign") if signs.len() < MAX_SIGNS => {
+                let lines = ["line1", "line2", "line3", "line4"].map(|n| {
+                    property(n).and_then(Json::as_str).unwrap_or_default().chars().take(MAX_USER_STRING_LEN).collect()
+                });
+                signs.push((pos, lines));
+            }
+            Some("door") if doors.len() < MAX_DOORS => {
+                let kind = property("kind").and_then(Json::as_usize).and_then(|k| DoorKind::try_from(k as u8).ok());
+                if let (Some(kind), Some(dest)) = (kind, property_pos("dest_x", "dest_y")) {
+                    doors.push((kind, pos, dest));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let property = |name: &str| -> String {
+        map.get("properties")
+            .and_then(|p| p.as_arr()?.iter().find(|p| p.get("name").and_then(Json::as_str) == Some(name))?.get("value")?.as_str())
+            .unwrap_or_default()
+            .chars().take(MAX_USER_STRING_LEN).collect()
+    };
+    let bg_col = map.get("backgroundcolor")
+        .and_then(Json::as_str)
+        .and_then(|c| u32::from_str_radix(c.trim_start_matches('#'), 16).ok())
+        .map(|c| ((c >> 16) as u8, (c >> 8) as u8, c as u8))
+        .unwrap_or(super::editor_level::BG_SKY);
+
+    let mut level = EditorLevel::new(
+        property("world"), property("name"), bg_col, width, height,
+        tiles, tiles_bg, signs, doors,
+        spawn.ok_or_else(|| String::from("Map has no spawn!"))?,
+        finish.ok_or_else(|| String::from("Map has no finish!"))?,
+        checkpoints, entities,
+    );
+    level.mark_modified();
+    Ok(level)
+}
+
+// Just enough json for Tiled maps
+enum Json {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    Arr(Vec<Json>),
+    Obj(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Obj(o) => o.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Num(n) => Some(*n),
+            _ => None,
+        }
+    }
+    fn as_usize(&self) -> Option<usize> {
+        self.as_f64().filter(|n| *n >= 0.0).map(|n| n as usize)
+    }
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+    fn as_arr(&self) -> Option<&Vec<Json>> {
+        match self {
+            Json::Arr(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    fn write(&self) -> String {
+        match self {
+            Json::Null => String::from("null"),
+            Json::Bool(b) => b.to_string(),
+            Json::Num(n) => n.to_string(),
+            Json::Str(s) => {
+                let mut out = String::from("\"");
+                for c in s.chars() {
+                    match c {
+                        '"'  => out.push_str("\\\""),
+                        '\\' => out.push_str("\\\\"),
+                        '\n' => out.push_str("\\n"),
+                        c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+                        c => out.push(c),
+                    }
+                }
+                out.push('"');
+                out
+            }
+            Json::Arr(a) => format!("[{}]", a.iter().map(Json::write).collect::<Vec<_>>().join(",")),
+            Json::Obj(o) => format!("{{{}}}", o.iter().map(|(k, v)| format!("{}:{}", Json::Str(k.clone()).write(), v.write())).collect::<Vec<_>>().join(",")),
+        }
+    }
+
+    fn parse(text: &str) -> Option<Json> {
+        let mut chars = text.chars().peekable();
+        let value = Self::parse_value(&mut chars)?;
+        Self::skip_whitespace(&mut chars);
+        chars.peek().is_none().then_some(value)
+    }
+
+    fn skip_whitespace(chars: &mut std::iter::Peekable<std::str::Chars>) {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+    }
+
+    fn parse_value(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<Json> {
+        Self::skip_whitespace(chars);
+        match *chars.peek()? {
+            '{' => {
+                chars.next();
+                let mut object = vec![];
+                loop {
+                    Self::skip_whitespace(chars);
+                    if chars.peek() == Some(&'}') && object.is_empty() {
+                        chars.next();
+                        break;
+                    }
+                    let Json::Str(key) = Self::parse_value(chars)? else {
+                        return None;
+                    };
+                    Self::skip_whitespace(chars);
+                    if chars.next()? != ':' {
+                        return None;
+                    }
+                    object.push((key, Self::parse_value(chars)?));
+                    Self::skip_whitespace(chars);
+                    match chars.next()? {
+                        ',' => continue,
+                        '}' => break,
+                        _ => return None,
+                    }
+                }
+                Some(Json::Obj(object))
+            }
+            '[' => {
+                chars.next();
+                let mut array = vec![];
+                loop {
+                    Self::skip_whitespace(chars);
+                    if chars.peek() == Some(&']') && array.is_empty() {
+                        chars.next();
+                        break;
+                    }
+                    array.push(Self::parse_value(chars)?);
+                    Self::skip_whitespace(chars);
+                    match chars.next()? {
+                        ',' => continue,
+                        ']' => break,
+                        _ => return None,
+                    }
+                }
+                Some(Json::Arr(array))
+            }
+            '"' => {
+                chars.next();
+                let mut string = String::new();
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => match chars.next()? {
+                            'n' => string.push('\n'),
+                            't' => string.push('\t'),
+                            'r' => string.push('\r'),
+                            'b' => string.push('\u{8}'),
+                            'f' => string.push('\u{c}'),
+                            'u' => {
+                                let hex: String = (0..4).filter_map(|_| chars.next()).collect();
+                                string.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?).unwrap_or('?'));
+                            }
+                            c => string.push(c),
+                        },
+                        c => string.push(c),
+                    }
+                }
+                Some(Json::Str(string))
+            }
+            't' | 'f' | 'n' => {
+                let mut word = String::new();
+                while chars.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                    word.push(chars.next()?);
+                }
+                match word.as_str() {
+                    "true"  => Some(Json::Bool(true)),
+                    "false" => Some(Json::Bool(false)),
+                    "null"  => Some(Json::Null),
+                    _ => None,
+                }
+            }
+            _ => {
+                let mut number = String::new();
+                while chars.peek().is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')) {
+                    number.push(chars.next()?);
+                }
+                number.parse().ok().map(Json::Num)
+            }
+        }
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\n', "&#10;")
+}
+
+// Just enough xml for Tiled maps too
+struct Xml {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Xml>,
+    text: String,
+}
+
+impl Xml {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Xml> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+    fn child(&self, name: &str) -> Option<&Xml> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    fn parse(text: &str) -> Option<Xml> {
+        let mut rest = text;
+        Self::skip_misc(&mut rest);
+        let root = Self::parse_element(&mut rest)?;
+        Self::skip_misc(&mut rest);
+        rest.is_empty().then_some(root)
+    }
+
+    // Whitespace, comments, and the '<?xml ... ?>' and '<!DOCTYPE ...>' bits
+    fn skip_misc(rest: &mut &str) {
+        loop {
+            *rest = rest.trim_start();
+            let end = if rest.starts_with("<?") {
+                rest.find("?>").map(|i| i + 2)
+            } else if rest.starts_with("<!--") {
+                rest.find("-->").map(|i| i + 3)
+            } else if rest.starts_with("<!") {
+                rest.find('>').map(|i| i + 1)
+            } else {
+                return;
+            };
+            *rest = &rest[end.unwrap_or(rest.len())..];
+        }
+    }
+
+    fn parse_element(rest: &mut &str) -> Option<Xml> {
+        *rest = rest.strip_prefix('<')?;
+        let name_end = rest.find(|c: char| c.is_whitespace() || c == '>' || c == '/')?;
+        let mut element = Xml { name: rest[..name_end].to_owned(), attributes: vec![], children: vec![], text: String::new() };
+        *rest = &rest[name_end..];
+
+        loop {
+            *rest = rest.trim_start();
+            if let Some(r) = rest.strip_prefix("/>") {
+                *rest = r;
+                return Some(element);
+            }
+            if let Some(r) = rest.strip_prefix('>') {
+                *rest = r;
+                break;
+            }
+            let equals = rest.find('=')?;
+            let key = rest[..equals].trim().to_owned();
+            *rest = rest[equals + 1..].trim_start();
+            let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            let end = rest[1..].find(quote)? + 1;
+            element.attributes.push((key, Self::unescape(&rest[1..end])));
+            *rest = &rest[end + 1..];
+        }
+
+        loop {
+            if let Some(r) = rest.strip_prefix("</") {
+                let end = r.find('>')?;
+                if r[..end].trim() != element.name {
+                    return None;
+                }
+                *rest = &r[end + 1..];
+                return Some(element);
+            }
+            if rest.starts_with("<!--") {
+                let end = rest.find("-->")?;
+                *rest = &rest[end + 3..];
+            } else if rest.starts_with('<') {
+                element.children.push(Self::parse_element(rest)?);
+            } else {
+                let end = rest.find('<')?;
+                element.text.push_str(&Self::unescape(&rest[..end]));
+                *rest = &rest[end..];
+            }
+        }
+    }
+
+    fn unescape(text: &str) -> String {
+        let mut out = String::new();
+        let mut rest = text;
+        while let Some(i) = rest.find('&') {
+            out.push_str(&rest[..i]);
+            rest = &rest[i..];
+            let c = rest.find(';').and_then(|end| {
+                let c = match &rest[1..end] {
+                    "lt"   => Some('<'),
+                    "gt"   => Some('>'),
+                    "amp"  => Some('&'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    e => match e.strip_prefix("#x") {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => e.strip_prefix('#').and_then(|d| d.parse().ok()),
+                    }.and_then(char::from_u32),
+                };
+                c.map(|c| (c, end))
+            });
+            match c {
+                Some((c, end)) => {
+                    out.push(c);
+                    rest = &rest[end + 1..];
+                }
+                // Not an entity, just a lone '&'
+                None => {
+                    out.push('&');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+}
@@ -0,0 +1,119 @@
+// Achievements! Unlocked by playing any pack, and saved so they stick around between sessions
+// Most of the progress towards them (signs read, chips collected, etc) only lasts for a play through of a pack,
+// but the stuff that adds up over time (goats stomped, lock colours opened) is saved too
+
+use super::{entity::EntityKind, events::GameEvent, level::tile::LockColor};
+use crate::ui::toast::{ToastKind, ToastManager};
+
+// Not a .fox file so it doesn't show up in the menu's pack list
+const FILE: &str = "achievements.foxsave";
+const GOATS_TO_STOMP: u32 = 100;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Achievement {
+    NoGameOver,
+    GoatStomper,
+    Locksmith,
+    Bookworm,
+    ChipCollector,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 5] = [Self::NoGameOver, Self::GoatStomper, Self::Locksmith, Self::Bookworm, Self::ChipCollector];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::NoGameOver    => "Flawless-ish",
+            Self::GoatStomper   => "Goat stomper",
+            Self::Locksmith     => "Locksmith",
+            Self::Bookworm      => "Bookworm",
+            Self::ChipCollector => "Chip collector",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::NoGameOver    => "Finish a pack without a game over",
+            Self::GoatStomper   => "Stomp 100 goats",
+            Self::Locksmith     => "Open every colour of lock",
+            Self::Bookworm      => "Read every sign in a pack",
+            Self::ChipCollector => "Collect every chip in a world",
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+#[derive(Default)]
+pub struct Achievements {
+    unlocked: u8,
+    goats_stomped: u32,
+    // A bit for each LockColor
+    locks_opened: u8,
+}
+
+impl Achievements {
+    // If the file doesn't exist (or is broken), nothing's been unlocked yet
+    pub fn load() -> Self {
+        let bytes = std::fs::read(FILE).unwrap_or_default();
+        match bytes.as_slice() {
+            [unlocked, locks_opened, goats @ ..] if goats.len() == 4 => Self {
+                unlocked: *unlocked,
+                locks_opened: *locks_opened,
+                goats_stomped: u32::from_le_bytes([goats[0], goats[1], goats[2], goats[3]]),
+            },
+            _ => Self::default(),
+        }
+    }
+
+    // The file is tiny so it's fine to save every time something changes
+    fn save(&self) {
+        let mut bytes = vec![self.unlocked, self.locks_opened];
+        bytes.extend_from_slice(&self.goats_stomped.to_le_bytes());
+        let _ = std::fs::write(FILE, bytes);
+    }
+
+    pub fn unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked & achievement.bit() != 0
+    }
+
+    // How far along the achievements that build up over time are
+    pub fn progress(&self, achievement: Achievement) -> Option<(u32, u32)> {
+        match achievement {
+            Achievement::GoatStomper => Some((self.goats_stomped.min(GOATS_TO_STOMP), GOATS_TO_STOMP)),
+            Achievement::Locksmith   => Some((self.locks_opened.count_ones(), LockColor::colors().len() as u32)),
+            _ => None,
+        }
+    }
+
+    pub fn unlock(&mut self, achievement: Achievement, toast_manager: &mut ToastManager) {
+        if self.unlocked(achievement) {
+            return;
+        }
+        self.unlocked |= achievement.bit();
+        self.save();
+        toast_manager.add_toast(format!("Achievement: {}!", achievement.name()), ToastKind::Success);
+    }
+
+    pub fn handle_event(&mut self, event: GameEvent, toast_manager: &mut ToastManager) {
+        match event {
+            GameEvent::EnemyStomped(EntityKind::Goat) => {
+                self.goats_stomped = self.goats_stomped.saturating_add(1);
+                self.save();
+                if self.goats_stomped >= GOATS_TO_STOMP {
+                    self.unlock(Achievement::GoatStomper, toast_manager);
+                }
+            }
+            GameEvent::LockOpened(color) => {
+                self.locks_opened |= 1 << color as u8;
+                self.save();
+                if self.locks_opened.count_ones() as usize == LockColor::colors().len() {
+                    self.unlock(Achievement::Locksmith, toast_manager);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,176 @@
+// Remembers where (and how) the player has died in each level of a pack, so the editor can show a heatmap of the deaths
+// Deaths from playtests and normal play both go in here, and they're saved next to the pack file
+
+use std::collections::HashMap;
+
+use macroquad::math::{vec2, Vec2};
+
+use super::entity::EntityKind;
+
+// Not a .fox file so it doesn't show up in the menu's pack list
+const EXTENSION: &str = "foxdeaths";
+// Only the most recent deaths in each level are kept so the file doesn't grow forever
+const MAX_DEATHS_PER_LEVEL: usize = 1024;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeathCause {
+    Fall, Lava, Spikes, Crushed,
+    Frog, Goat, Armadillo, Cannonball, Fireball, FlameJet, DangerCloud, Explosion,
+    Other,
+}
+
+impl DeathCause {
+    const ALL: [DeathCause; 13] = [
+        Self::Fall, Self::Lava, Self::Spikes, Self::Crushed,
+        Self::Frog, Self::Goat, Self::Armadillo, Self::Cannonball, Self::Fireball, Self::FlameJet, Self::DangerCloud, Self::Explosion,
+        Self::Other,
+    ];
+
+    pub fn from_entity(kind: EntityKind) -> Self {
+        match kind {
+            EntityKind::Frog(_)       => Self::Frog,
+            EntityKind::Goat          => Self::Goat,
+            EntityKind::Armadillo(..) => Self::Armadillo,
+            EntityKind::Cannonball    => Self::Cannonball,
+            EntityKind::Fireball      => Self::Fireball,
+            EntityKind::FlameJet(_)   => Self::FlameJet,
+            EntityKind::DangerCloud   => Self::DangerCloud,
+            EntityKind::Explosion     => Self::Explosion,
+            _ => Self::Other,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Fall        => "Fall",
+            Self::Lava        => "Lava",
+            Self::Spikes      => "Spikes",
+            Self::Crushed     => "Crushed",
+            Self::Frog        => "Frog",
+            Self::Goat        => "Goat",
+            Self::Armadillo   => "Armadillo",
+            Self::Cannonball  => "Cannonball",
+            Self::Fireball    => "Fireball",
+            Self::FlameJet    => "Flame jet",
+            Self::DangerCloud => "Danger cloud",
+            Self::Explosion   => "Explosion",
+            Self::Other       => "Other",
+        }
+    }
+
+    fn from_byte(byte: u8) -> Self {
+        Self::ALL.get(byte as usize).copied().unwrap_or(Self::Other)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Death {
+    // The center of the player when they died
+    pub pos: Vec2,
+    pub cause: DeathCause,
+}
+
+#[derive(Default)]
+pub struct DeathMap {
+    pack_file_name: String,
+    // Keyed by the index of the level in the pack
+    levels: HashMap<usize, Vec<Death>>,
+}
+
+impl DeathMap {
+    fn path(pack_file_name: &str) -> String {
+        format!("{pack_file_name}.{EXTENSION}")
+    }
+
+    // If the file doesn't exist (or is broken), there just aren't any deaths yet
+    pub fn load(pack_file_name: &str) -> Self {
+        let levels = std::fs::read(Self::path(pack_file_name))
+            .ok()
+            .and_then(|bytes| Self::from_bytes(&bytes))
+            .unwrap_or_default();
+        Self { pack_file_name: pack_file_name.to_owned(), levels }
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        // A pack that hasn't been saved yet has nowhere for its deaths to go
+        if self.pack_file_name.is_empty() {
+            return Ok(());
+        }
+        std::fs::write(Self::path(&self.pack_file_name), self.to_bytes())
+    }
+
+    pub fn pack_file_name(&self) -> &String {
+        &self.pack_file_name
+    }
+    // For when the pack is saved under a different name
+    pub fn set_pack_file_name(&mut self, pack_file_name: String) {
+        self.pack_file_name = pack_file_name;
+    }
+
+    pub fn deaths(&self, level: usize) -> &[Death] {
+        self.levels.get(&level).map(|d| d.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn record(&mut self, level: usize, death: Death) {
+        let deaths = self.levels.entry(level).or_default();
+        deaths.push(death);
+        if deaths.len() > MAX_DEATHS_PER_LEVEL {
+            deaths.remove(0);
+        }
+    }
+
+    pub fn clear_level(&mut self, level: usize) {
+        self.levels.remove(&level);
+    }
+
+    // For when the levels in a pack have been moved around
+    // 'old_indices' has where each level used to be, levels that didn't exist before (and ones that are gone now) have their deaths dropped
+    pub fn remap(&mut self, old_indices: &[Option<usize>]) {
+        let mut old_levels = std::mem::take(&mut self.levels);
+        for (new, old) in old_indices.iter().enumerate() {
+            if let Some(deaths) = old.and_then(|old| old_levels.remove(&old)) {
+                self.levels.insert(new, deaths);
+            }
+        }
+    }
+
+    // Each level is stored as:
+    // - level index (u8)
+    // - death count (u16)
+    // - that many deaths, each being x (u16), y (u16), cause (u8)
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        for (level, deaths) in &self.levels {
+            bytes.push(*level as u8);
+            bytes.extend_from_slice(&(deaths.len() as u16).to_le_bytes());
+            for death in deaths {
+                bytes.extend_from_slice(&(death.pos.x.max(0.0) as u16).to_le_bytes());
+                bytes.extend_from_slice(&(death.pos.y.max(0.0) as u16).to_le_bytes());
+                bytes.push(death.cause as u8);
+            }
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<HashMap<usize, Vec<Death>>> {
+        let mut levels = HashMap::new();
+        let mut cursor = 0;
+        let read_u16 = |cursor: usize| -> Option<u16> {
+            Some(u16::from_le_bytes(bytes.get(cursor..cursor+2)?.try_into().ok()?))
+        };
+        while cursor < bytes.len() {
+            let level = bytes[cursor] as usize;
+            let count = read_u16(cursor + 1)? as usize;
+            cursor += 3;
+            let mut deaths = Vec::with_capacity(count);
+            for _ in 0..count {
+                let pos = vec2(read_u16(cursor)? as f32, read_u16(cursor + 2)? as f32);
+                let cause = DeathCause::from_byte(*bytes.get(cursor + 4)?);
+                deaths.push(Death { pos, cause });
+                cursor += 5;
+            }
+            levels.insert(level, deaths);
+        }
+        Some(levels)
+    }
+}
@@ -0,0 +1,67 @@
+// How hard a play through of a pack is, picked in the menu after pressing play
+
+use macroquad::color::{Color, GREEN, RED, WHITE};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Difficulty {
+    Casual,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Self::Casual, Self::Normal, Self::Hard];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Casual => "Casual",
+            Self::Normal => "Normal",
+            Self::Hard   => "Hard",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Casual => "Infinite lives, no game overs",
+            Self::Normal => "How the pack was made to be played",
+            Self::Hard   => "One life per world, no checkpoints",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Self::Casual => GREEN,
+            Self::Normal => WHITE,
+            Self::Hard   => RED,
+        }
+    }
+
+    // Lives start at this and go back to it after a game over
+    // (0 means the first death is a game over)
+    pub fn starting_lives(self) -> usize {
+        match self {
+            Self::Hard => 0,
+            _ => 3,
+        }
+    }
+
+    // Lives never running out means there's never a game over to send you back to the start of the world
+    pub fn infinite_lives(self) -> bool {
+        self == Self::Casual
+    }
+
+    // Hard mode doesn't let you collect lives either, that'd make it not 'one life per world'
+    pub fn lives_collectable(self) -> bool {
+        self != Self::Hard
+    }
+
+    pub fn checkpoints(self) -> bool {
+        self != Self::Hard
+    }
+
+    // Getting hurt kills you, even with powerups
+    pub fn hurt_kills(self) -> bool {
+        self == Self::Hard
+    }
+}
@@ -24,6 +24,7 @@ enum State {
     Dead(f32),
 }
 
+#[derive(Clone)]
 pub struct Armadillo {
     id: Id,
     pos: Vec2,
@@ -38,7 +39,7 @@ pub struct Armadillo {
 }
 
 impl Armadillo {
-    pub fn new(pos: Vec2, vel: Vec2, spinning: bool, invuln: bool, id: Id) -> Self {
+    pub fn new(pos: Vec2, vel: Vec2, spinning: bool, invuln: bool, facing: Option<Dir>, id: Id) -> Self {
         let invuln = match invuln {
             true  => Some(1.0),
             false => None,
@@ -53,7 +54,7 @@ impl Armadillo {
             vel,
             invuln,
             speed: 0.0,
-            dir: if rand() % 2 == 0 { Dir::Left } else { Dir::Right },
+            dir: facing.unwrap_or(if rand() % 2 == 0 { Dir::Left } else { Dir::Right }),
             state,
             in_air: true,
             spin_timer: 0.0,
@@ -123,6 +124,9 @@ impl Entity for Armadillo {
     fn id(&self) -> Id {
         self.id
     }
+    fn snapshot(&self) -> Box<dyn Entity> {
+        Box::new(self.clone())
+    }
     fn kind(&self) -> EntityKind {
         EntityKind::Armadillo(self.invuln.is_some(), matches!(self.state, State::Spinning(_)))
     }
@@ -4,6 +4,7 @@ use crate::game::scene::{GRAVITY, MAX_FALL_SPEED};
 
 use super::{Entity, EntityKind, Id};
 
+#[derive(Clone)]
 pub struct Cannonball {
     id: Id,
     pos: Vec2,
@@ -30,6 +31,9 @@ impl Entity for Cannonball {
     fn id(&self) -> Id {
         self.id
     }
+    fn snapshot(&self) -> Box<dyn Entity> {
+        Box::new(self.clone())
+    }
     fn kind(&self) -> EntityKind {
         EntityKind::Cannonball
     }
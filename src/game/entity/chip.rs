@@ -13,6 +13,7 @@ const BOT_L:   Vec2 = vec2( 4.0, 14.0);
 const BOT_R:   Vec2 = vec2(12.0, 14.0);
 const CENTER:  Vec2 = vec2( 8.0,  7.0);
 
+#[derive(Clone)]
 pub struct Chip {
     id: Id,
     pos: Vec2,
@@ -61,6 +62,9 @@ impl Entity for Chip {
     fn id(&self) -> Id {
         self.id
     }
+    fn snapshot(&self) -> Box<dyn Entity> {
+        Box::new(self.clone())
+    }
     fn kind(&self) -> EntityKind {
         match self.life {
             true  => EntityKind::Life(self.vel.is_some()),
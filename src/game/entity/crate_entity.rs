@@ -28,6 +28,7 @@ pub enum CrateKind {
     Explosive,
 }
 
+#[derive(Clone)]
 pub struct Crate {
     id: Id,
     pos: Vec2,
@@ -202,6 +203,9 @@ impl Entity for Crate {
     fn id(&self) -> Id {
         self.id
     }
+    fn snapshot(&self) -> Box<dyn Entity> {
+        Box::new(self.clone())
+    }
     fn kind(&self) -> EntityKind {
         EntityKind::Crate(self.kind)
     }
@@ -4,6 +4,7 @@ use crate::{game::{level::Level, player::Player, scene::{camera::Camera, entity_
 
 use super::{Entity, EntityKind, Id};
 
+#[derive(Clone)]
 pub struct DangerCloud {
     pos: Vec2,
     vel: Vec2,
@@ -23,6 +24,9 @@ impl Entity for DangerCloud {
     fn id(&self) -> Id {
         self.id
     }
+    fn snapshot(&self) -> Box<dyn Entity> {
+        Box::new(self.clone())
+    }
     fn kind(&self) -> EntityKind {
         EntityKind::DangerCloud
     }
@@ -2,12 +2,13 @@ use std::f32::consts::PI;
 
 use macroquad::{math::{vec2, Rect, Vec2}, rand::gen_range};
 
-use crate::{game::{level::tile::TileHitKind, player::Player, scene::{camera::Camera, particles::{ParticleKind, Particles}}}, resources::Resources, util::rect};
+use crate::{game::{death_map::DeathCause, level::tile::TileHitKind, player::Player, scene::{camera::Camera, particles::{ParticleKind, Particles}}}, resources::Resources, util::rect};
 
 use super::{crate_entity::CrateKind, Entity, EntityKind, Id};
 
 const SIZE: Vec2 = vec2(48.0, 48.0);
 
+#[derive(Clone)]
 pub struct Explosion {
     center: Vec2,
     timer: f32,
@@ -36,6 +37,9 @@ impl Entity for Explosion {
     fn id(&self) -> Id {
         self.id
     }
+    fn snapshot(&self) -> Box<dyn Entity> {
+        Box::new(self.clone())
+    }
     fn kind(&self) -> EntityKind {
         EntityKind::Explosion
     }
@@ -102,7 +106,7 @@ impl Entity for Explosion {
             }
         }
         if player.chip_hitbox().overlaps(&player_rect) {
-            player.hurt();
+            player.hurt(DeathCause::Explosion);
         }
 
         for offset in [
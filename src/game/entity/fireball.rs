@@ -4,6 +4,7 @@ use crate::game::scene::{GRAVITY, MAX_FALL_SPEED};
 
 use super::{Entity, EntityKind, Id};
 
+#[derive(Clone)]
 pub struct Fireball {
     id: Id,
     pos: Vec2,
@@ -21,6 +22,9 @@ impl Entity for Fireball {
     fn id(&self) -> Id {
         self.id
     }
+    fn snapshot(&self) -> Box<dyn Entity> {
+        Box::new(self.clone())
+    }
     fn kind(&self) -> EntityKind {
         EntityKind::Fireball
     }
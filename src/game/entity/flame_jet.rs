@@ -1,23 +1,27 @@
 // 176.0 96.0
 
-use macroquad::{color::WHITE, math::{vec2, Rect, Vec2}};
+use macroquad::{color::{Color, WHITE}, math::{vec2, Rect, Vec2}};
 
 use crate::{game::scene::entity_spawner::EntitySpawner, resources::Resources};
 
-use super::{Entity, EntityKind, Id};
+use super::{props::TimingState, Entity, EntityKind, Id};
 
 const GAS_BEG_TIME: f64 = 2.0;
 const ON_TIME:      f64 = 3.0;
 const GAS_END_TIME: f64 = 6.0;
-const TOTAL_TIME:   f64 = 6.05;
+pub const TOTAL_TIME: f64 = 6.05;
 
 // if flaming move in/out as well as flip horizontally
 // flip, in, flip, out
 
+#[derive(Clone)]
 pub struct FlameJet {
     id: Id,
     pos: Vec2,
     dir: bool,
+    // Where in the cycle it starts, and how long the cycle is
+    phase: f64,
+    period: f64,
 
     active: bool,
     first: bool,
@@ -25,17 +29,29 @@ pub struct FlameJet {
 }
 
 impl FlameJet {
-    pub fn new(pos: Vec2, dir: bool, id: Id) -> Self {
-        Self { id, pos, dir, active: false, first: true, second: true }
+    pub fn new(pos: Vec2, dir: bool, phase: f64, period: Option<f64>, id: Id) -> Self {
+        Self { id, pos, dir, phase, period: period.unwrap_or(TOTAL_TIME), active: false, first: true, second: true }
     }
 
-    pub fn draw_editor(dir: bool, pos: Vec2, camera_pos: Vec2, resources: &Resources) {
+    // The time in the cycle, scaled so the cycle is always TOTAL_TIME long
+    fn cycle_time(time: f64, phase: f64, period: f64) -> f64 {
+        ((time + phase) % period) * TOTAL_TIME / period
+    }
+    pub fn timing_state(time: f64, phase: f64, period: f64) -> TimingState {
+        match Self::cycle_time(time, phase, period) {
+            t if (ON_TIME..GAS_END_TIME).contains(&t) => TimingState::On,
+            t if t >= GAS_BEG_TIME => TimingState::Warning,
+            _ => TimingState::Off,
+        }
+    }
+
+    pub fn draw_editor(dir: bool, pos: Vec2, camera_pos: Vec2, color: Color, resources: &Resources) {
         let x_offset = match dir {
             false => 0.0,
             true  => 16.0,
         };
         let rect = Rect::new(176.0 + x_offset, 96.0, 16.0, 16.0);
-        resources.draw_rect(pos - camera_pos, rect, false, false, WHITE, resources.entity_atlas());
+        resources.draw_rect(pos - camera_pos, rect, false, false, color, resources.entity_atlas());
     }
 }
 
@@ -43,6 +59,9 @@ impl Entity for FlameJet {
     fn id(&self) -> Id {
         self.id
     }
+    fn snapshot(&self) -> Box<dyn Entity> {
+        Box::new(self.clone())
+    }
     fn kind(&self) -> EntityKind {
         EntityKind::FlameJet(self.dir)
     }
@@ -104,15 +123,14 @@ impl Entity for FlameJet {
             true  => (vec2(8.0, 24.0), vec2(8.0, -8.0)),
         };
 
-        let t = resources.tile_animation_timer() % TOTAL_TIME;
-        self.active = t >= ON_TIME && t < GAS_END_TIME;
+        self.active = Self::timing_state(resources.tile_animation_timer(), self.phase, self.period) == TimingState::On;
         self.first  = !resources.tile_data(level.tile_at_pos(self.pos + first_check)) .collision().is_solid();
         self.second = !resources.tile_data(level.tile_at_pos(self.pos + second_check)).collision().is_solid();
     }
 
     fn draw(&self, _player: &crate::game::player::Player, camera_pos: Vec2, resources: &Resources) {
         // Only draw if we should... duh..
-        if resources.tile_animation_timer() % TOTAL_TIME < GAS_BEG_TIME {
+        if Self::timing_state(resources.tile_animation_timer(), self.phase, self.period) == TimingState::Off {
             return;
         }
 
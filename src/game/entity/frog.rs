@@ -13,12 +13,14 @@ const CENTER: Vec2 = vec2( 6.0,  8.0);
 
 const SHAKE_TIME: f32 = 0.5;
 
+#[derive(Clone)]
 enum State {
     Waiting(f32), // Time left
     Air,
     Dead(f32),
 }
 
+#[derive(Clone)]
 pub struct Frog {
     id: Id,
     pos: Vec2,
@@ -79,6 +81,9 @@ impl Entity for Frog {
     fn id(&self) -> Id {
         self.id
     }
+    fn snapshot(&self) -> Box<dyn Entity> {
+        Box::new(self.clone())
+    }
     fn kind(&self) -> EntityKind {
         EntityKind::Frog(self.invuln.is_some())
     }
@@ -18,6 +18,7 @@ const RIGHT_BOT: Vec2 = vec2(13.0, 28.0);
 const CENTER_TOP: Vec2 = vec2( 8.0, 10.0);
 const CENTER_BOT: Vec2 = vec2( 8.0, 24.0);
 
+#[derive(Clone)]
 pub struct Goat {
     id: Id,
     pos: Vec2,
@@ -35,7 +36,7 @@ pub struct Goat {
     step_anim: f32,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 enum State {
     // When idle, the goat is still
     Idle(f32),
@@ -51,7 +52,8 @@ enum Arm {
 }
 
 impl Goat {
-    pub fn new(pos: Vec2, vel: Vec2, id: Id) -> Self {
+    pub fn new(pos: Vec2, vel: Vec2, facing: Option<Dir>, health: Option<u8>, id: Id) -> Self {
+        let random_dir = || if rand() % 2 == 0 { Dir::Left } else { Dir::Right };
         Self {
             id,
             pos,
@@ -59,10 +61,10 @@ impl Goat {
             target_x_vel: vel.x,
             state: State::Idle(gen_range(0.5, 1.0)),
             in_air: true,
-            facing:      if rand() % 2 == 0 { Dir::Left } else { Dir::Right },
-            next_facing: if rand() % 2 == 0 { Dir::Left } else { Dir::Right },
+            facing:      facing.unwrap_or_else(random_dir),
+            next_facing: facing.unwrap_or_else(random_dir),
             invuln: None,
-            health: 1,
+            health: health.unwrap_or(1),
 
             arm: Arm::Down,
             step_anim: 0.0,
@@ -109,6 +111,9 @@ impl Entity for Goat {
     fn id(&self) -> Id {
         self.id
     }
+    fn snapshot(&self) -> Box<dyn Entity> {
+        Box::new(self.clone())
+    }
     fn kind(&self) -> EntityKind {
         EntityKind::Goat
     }
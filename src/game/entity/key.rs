@@ -14,6 +14,7 @@ const BOT_M:   Vec2 = vec2( 8.0, 14.0);
 const BOT_R:   Vec2 = vec2(11.0, 14.0);
 const CENTER:  Vec2 = vec2( 8.0,  7.0);
 
+#[derive(Clone)]
 pub struct Key {
     id: Id,
     pos: Vec2,
@@ -51,6 +52,9 @@ impl Entity for Key {
     fn id(&self) -> Id {
         self.id
     }
+    fn snapshot(&self) -> Box<dyn Entity> {
+        Box::new(self.clone())
+    }
     fn kind(&self) -> EntityKind {
         EntityKind::Key(self.color)
     }
@@ -1,26 +1,41 @@
-use macroquad::{color::WHITE, math::{vec2, Rect, Vec2}};
+use macroquad::{color::Color, math::{vec2, Rect, Vec2}};
 
 use crate::{game::{level::tile::TileDir, scene::entity_spawner::EntitySpawner}, resources::Resources};
 
-use super::{Entity, EntityKind, Id};
+use super::{props::TimingState, Entity, EntityKind, Id};
+
+const FIRE_WINDOW: f64 = 0.1;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum LauncherKind {
     Cannonball(TileDir), Fireball,
 }
 
+#[derive(Clone)]
 pub struct Launcher {
     id: Id,
     pos: Vec2,
     kind: LauncherKind,
     fired: bool,
+    // Where in the cycle it starts, and how long between each shot
+    phase: f64,
+    interval: f64,
 }
 
 impl Launcher {
-    pub fn new(kind: LauncherKind, pos: Vec2, id: Id) -> Self {
-        Self { id, pos, fired: true, kind }
+    pub fn new(kind: LauncherKind, pos: Vec2, phase: f64, interval: Option<f64>, id: Id) -> Self {
+        let interval = interval.unwrap_or(EntityKind::Launcher(kind).default_period());
+        Self { id, pos, fired: true, kind, phase, interval }
+    }
+    // Launchers fire right at the start of each interval
+    pub fn timing_state(time: f64, phase: f64, interval: f64) -> TimingState {
+        match (time + phase) % interval < FIRE_WINDOW {
+            true  => TimingState::On,
+            false => TimingState::Off,
+        }
     }
-    pub fn draw_editor(kind: LauncherKind, pos: Vec2, camera_pos: Vec2, resources: &Resources) {
+
+    pub fn draw_editor(kind: LauncherKind, pos: Vec2, camera_pos: Vec2, color: Color, resources: &Resources) {
         let x_offset = match kind {
             LauncherKind::Cannonball(TileDir::Left)   => 16.0,
             LauncherKind::Cannonball(TileDir::Right)  => 32.0,
@@ -29,7 +44,7 @@ impl Launcher {
             LauncherKind::Fireball => 0.0,
         };
         let rect = Rect::new(176.0 + x_offset, 80.0, 16.0, 16.0);
-        resources.draw_rect(pos - camera_pos, rect, false, false, WHITE, resources.entity_atlas());
+        resources.draw_rect(pos - camera_pos, rect, false, false, color, resources.entity_atlas());
     }
 }
 
@@ -37,6 +52,9 @@ impl Entity for Launcher {
     fn id(&self) -> Id {
         self.id
     }
+    fn snapshot(&self) -> Box<dyn Entity> {
+        Box::new(self.clone())
+    }
     fn kind(&self) -> EntityKind {
         EntityKind::Launcher(self.kind)
     }
@@ -61,13 +79,7 @@ impl Entity for Launcher {
     }
 
     fn physics_update(&mut self, _player: &mut crate::game::player::Player, _others: &mut Vec<&mut Box<dyn Entity>>, entity_spawner: &mut EntitySpawner, _particles: &mut crate::game::scene::particles::Particles, _level: &mut crate::game::level::Level, camera: &mut crate::game::scene::camera::Camera, resources: &Resources) {
-        let update_time = match self.kind {
-            LauncherKind::Cannonball(_) => 3.0,
-            LauncherKind::Fireball => 4.0,
-        };
-        let t = resources.tile_animation_timer();
-
-        if t % update_time < 0.1 {
+        if Self::timing_state(resources.tile_animation_timer(), self.phase, self.interval) == TimingState::On {
             self.fired = false;
             return;
         }
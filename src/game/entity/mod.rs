@@ -26,6 +26,7 @@ pub mod launcher;
 pub mod cannonball;
 pub mod fireball;
 pub mod flame_jet;
+pub mod props;
 // TODO: Tumbleweed maybe? for desert levels
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -37,6 +38,8 @@ pub enum Id {
 pub trait Entity {
     // General entity stuff
     fn id(&self) -> Id;
+    // A copy of the entity as it is right now, for quicksaves
+    fn snapshot(&self) -> Box<dyn Entity>;
     fn kind(&self) -> EntityKind;
     fn hitbox(&self) -> Rect;
     // Some entities won't be hurt or stomped
@@ -105,6 +108,13 @@ pub trait Entity {
     fn draw(&self, _player: &Player, camera_pos: Vec2, resources: &Resources);
 }
 
+// Lets anything holding entities (the scene, the player, etc) be cloned
+impl Clone for Box<dyn Entity> {
+    fn clone(&self) -> Self {
+        self.snapshot()
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum EntityKind {
     Crate(CrateKind),
@@ -204,7 +214,7 @@ impl EntityKind {
         }
     }
     // Draw the entity kind, for use in the editor
-    pub fn draw_editor(&self, transparent: bool, in_view: bool, entity_pos: Vec2, camera_pos: Vec2, resources: &Resources) {
+    pub fn draw_editor(&self, opacity: f32, in_view: bool, entity_pos: Vec2, camera_pos: Vec2, resources: &Resources) {
         // Work out the position of the entity, it's position in the object selector could be different to in the level view.
         let pos = match in_view {
             // Position it based on the offset
@@ -212,7 +222,7 @@ impl EntityKind {
             false => entity_pos + self.object_selector_offset(),
         };
 
-        let color = Color::new(1.0, 1.0, 1.0, if transparent { 0.5 } else { 1.0 });
+        let color = Color::new(1.0, 1.0, 1.0, opacity);
 
         match self {
             // If it's a crate.. it's a bit more difficult!!
@@ -225,8 +235,8 @@ impl EntityKind {
             EntityKind::Goat => Goat::draw_editor(pos, camera_pos, color, resources),
             EntityKind::Armadillo(_, false) => Armadillo::draw_editor(pos, None, camera_pos, color, resources),
             EntityKind::Armadillo(_, true)  => Armadillo::draw_editor(pos, Some(resources.tile_animation_timer() as f32), camera_pos, color, resources),
-            EntityKind::Launcher(kind) => Launcher::draw_editor(*kind, pos, camera_pos, resources),
-            EntityKind::FlameJet(direction) => FlameJet::draw_editor(*direction, pos, camera_pos, resources),
+            EntityKind::Launcher(kind) => Launcher::draw_editor(*kind, pos, camera_pos, color, resources),
+            EntityKind::FlameJet(direction) => FlameJet::draw_editor(*direction, pos, camera_pos, color, resources),
             _ => {}
         }
     }
@@ -13,6 +13,7 @@ const BOT_L:   Vec2 = vec2( 6.0, 16.0);
 const BOT_R:   Vec2 = vec2(12.0, 16.0);
 const CENTER:  Vec2 = vec2( 9.0,  8.0);
 
+#[derive(Clone)]
 pub struct Powerup {
     id: Id,
     pos: Vec2,
@@ -66,6 +67,9 @@ impl Entity for Powerup {
     fn id(&self) -> Id {
         self.id
     }
+    fn snapshot(&self) -> Box<dyn Entity> {
+        Box::new(self.clone())
+    }
     fn kind(&self) -> EntityKind {
         EntityKind::Powerup(self.kind, self.vel.is_some(), self.invuln.is_some())
     }
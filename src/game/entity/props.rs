@@ -0,0 +1,206 @@
+// Extra settings that each placed entity can have, changed with the inspector in the editor
+// Everything is stored as a byte so it's easy to save, and 0 always means 'do the normal thing'
+// so old levels (and entities that don't care) behave exactly as before
+
+use macroquad::math::{vec2, Vec2};
+
+use crate::game::player::Dir;
+
+use super::{flame_jet::FlameJet, launcher::{Launcher, LauncherKind}, EntityKind};
+
+// How many bytes the props take up in the file, for each pack version
+pub fn props_len(version: u8) -> usize {
+    match version {
+        0 => 0,
+        1 => 6,
+        _ => 7,
+    }
+}
+
+// Timings are stored in tenths of a second
+const TIME_UNIT: f64 = 0.1;
+// Velocities are stored in sixteenths of a pixel per physics step
+const VEL_UNIT: f32 = 1.0 / 16.0;
+const MAX_VEL: i8 = 64;
+const MAX_HEALTH: u8 = 9;
+pub const MAX_GROUP: u8 = 8;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct EntityProps {
+    // 0 = random, 1 = left, 2 = right
+    pub facing: u8,
+    // How far into its cycle a timed entity starts
+    pub phase: u8,
+    // How long a timed entity's cycle is, 0 = default
+    pub period: u8,
+    // 0 = default
+    pub health: u8,
+    pub vel_x: i8,
+    pub vel_y: i8,
+    // Timed entities in the same group share a period (0 = no group)
+    pub group: u8,
+}
+
+// What a timed entity is doing at some point in its cycle, for the timeline in the editor
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimingState {
+    Off, Warning, On,
+}
+
+// The props that can be changed in the inspector
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EntityProp {
+    Facing, Phase, Period, Group, Health, VelX, VelY,
+}
+
+impl EntityProps {
+    pub fn facing(&self) -> Option<Dir> {
+        match self.facing {
+            1 => Some(Dir::Left),
+            2 => Some(Dir::Right),
+            _ => None,
+        }
+    }
+    pub fn phase(&self) -> f64 {
+        self.phase as f64 * TIME_UNIT
+    }
+    pub fn period(&self) -> Option<f64> {
+        match self.period {
+            0 => None,
+            p => Some(p as f64 * TIME_UNIT),
+        }
+    }
+    // The stored period, but with the default filled in if it's not been set
+    pub fn period_or_default(&self, kind: EntityKind) -> u8 {
+        match self.period {
+            0 => (kind.default_period() / TIME_UNIT).round() as u8,
+            p => p,
+        }
+    }
+    pub fn health(&self) -> Option<u8> {
+        match self.health {
+            0 => None,
+            h => Some(h),
+        }
+    }
+    pub fn flip_facing(&mut self) {
+        self.facing = match self.facing {
+            1 => 2,
+            2 => 1,
+            f => f,
+        };
+    }
+    pub fn vel(&self) -> Vec2 {
+        vec2(self.vel_x as f32, self.vel_y as f32) * VEL_UNIT
+    }
+
+    pub fn to_bytes(&self) -> [u8; 7] {
+        [self.facing, self.phase, self.period, self.health, self.vel_x as u8, self.vel_y as u8, self.group]
+    }
+    // Clamps everything so dodgy files can't make anything weird
+    // Older versions have less props, the missing ones are left as 0
+    pub fn from_bytes(bytes: &[u8], version: u8) -> Option<Self> {
+        let mut b = [0; 7];
+        let len = props_len(version);
+        b[..len].copy_from_slice(bytes.get(..len)?);
+        Some(Self {
+            facing: b[0].min(2),
+            phase:  b[1],
+            period: b[2],
+            health: b[3].min(MAX_HEALTH),
+            vel_x: (b[4] as i8).clamp(-MAX_VEL, MAX_VEL),
+            vel_y: (b[5] as i8).clamp(-MAX_VEL, MAX_VEL),
+            group: b[6].min(MAX_GROUP),
+        })
+    }
+}
+
+impl EntityProp {
+    pub fn name(self, kind: EntityKind) -> &'static str {
+        match self {
+            Self::Facing => "Facing",
+            Self::Phase  => "Phase",
+            Self::Period if matches!(kind, EntityKind::Launcher(_)) => "Interval",
+            Self::Period => "Period",
+            Self::Group  => "Group",
+            Self::Health => "Health",
+            Self::VelX   => "Speed x",
+            Self::VelY   => "Speed y",
+        }
+    }
+
+    pub fn value_text(self, kind: EntityKind, props: &EntityProps) -> String {
+        match self {
+            Self::Facing => String::from(match props.facing() {
+                None            => "Random",
+                Some(Dir::Left)  => "Left",
+                Some(Dir::Right) => "Right",
+            }),
+            Self::Phase  => format!("{:.1}s", props.phase()),
+            Self::Period => format!("{:.1}s", props.period().unwrap_or(kind.default_period())),
+            Self::Group  => match props.group {
+                0 => String::from("None"),
+                g => format!("{g}"),
+            },
+            Self::Health => format!("{}", props.health().unwrap_or(1)),
+            Self::VelX   => format!("{:.2}", props.vel().x),
+            Self::VelY   => format!("{:.2}", props.vel().y),
+        }
+    }
+
+    // Changes the prop by some amount of steps, keeping it in range
+    pub fn step(self, kind: EntityKind, props: &mut EntityProps, amount: i32) {
+        let step_vel = |v: i8| (v as i32 + amount).clamp(-MAX_VEL as i32, MAX_VEL as i32) as i8;
+        match self {
+            Self::Facing => props.facing = (props.facing as i32 + amount).rem_euclid(3) as u8,
+            Self::Phase  => props.phase  = (props.phase as i32 + amount).clamp(0, 255) as u8,
+            Self::Period => {
+                // The default period is where we start stepping from
+                props.period = (props.period_or_default(kind) as i32 + amount).clamp(10, 255) as u8;
+            }
+            Self::Group  => props.group = (props.group as i32 + amount).clamp(0, MAX_GROUP as i32) as u8,
+            Self::Health => props.health = (props.health().unwrap_or(1) as i32 + amount).clamp(1, MAX_HEALTH as i32) as u8,
+            Self::VelX   => props.vel_x = step_vel(props.vel_x),
+            Self::VelY   => props.vel_y = step_vel(props.vel_y),
+        }
+    }
+}
+
+impl EntityKind {
+    // The props that actually do something for this kind of entity
+    pub fn props(self) -> &'static [EntityProp] {
+        use EntityProp::*;
+        match self {
+            Self::Goat => &[Facing, Health, VelX, VelY],
+            Self::Armadillo(..) => &[Facing, VelX, VelY],
+            Self::Launcher(_) | Self::FlameJet(_) => &[Phase, Period, Group],
+            // Things without gravity don't move, so there's no point in giving them a velocity
+            Self::Powerup(_, false, _) | Self::Chip(false) | Self::Life(false) => &[],
+            Self::Crate(_) | Self::Key(_) | Self::Powerup(..) | Self::Chip(_) | Self::Life(_) | Self::Frog(_) => &[VelX, VelY],
+            _ => &[],
+        }
+    }
+
+    // How long the cycle of a timed entity is if it's not been changed
+    pub fn default_period(self) -> f64 {
+        match self {
+            Self::Launcher(LauncherKind::Cannonball(_)) => 3.0,
+            Self::Launcher(LauncherKind::Fireball) => 4.0,
+            Self::FlameJet(_) => super::flame_jet::TOTAL_TIME,
+            _ => 0.0,
+        }
+    }
+
+    pub fn timed(self) -> bool {
+        matches!(self, Self::Launcher(_) | Self::FlameJet(_))
+    }
+    // What a timed entity with these props would be doing at some time
+    pub fn timing_state(self, props: &EntityProps, time: f64) -> TimingState {
+        let period = props.period().unwrap_or(self.default_period());
+        match self {
+            Self::FlameJet(_) => FlameJet::timing_state(time, props.phase(), period),
+            Self::Launcher(_) => Launcher::timing_state(time, props.phase(), period),
+            _ => TimingState::Off,
+        }
+    }
+}
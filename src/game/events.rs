@@ -0,0 +1,35 @@
+// Things that happen while playing a level
+// The scene (and the player/entities/level in it) adds these to a queue, and whatever's running the scene
+// takes them out each frame to do stuff with, e.g. updating the chip counter, saving stats, etc...
+
+use super::{death_map::DeathCause, entity::EntityKind, level::tile::LockColor, player::PowerupKind};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameEvent {
+    ChipCollected,
+    LifeCollected,
+    PowerupCollected(PowerupKind),
+    EnemyStomped(EntityKind),
+    TileBroken,
+    LockOpened(LockColor),
+    DoorUsed,
+    Checkpoint(usize),
+    PlayerDied(DeathCause),
+    PracticeCheckpoint,
+}
+
+#[derive(Default, Clone)]
+pub struct GameEvents {
+    events: Vec<GameEvent>,
+}
+
+impl GameEvents {
+    pub fn push(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    // Empties the queue, returning everything that happened since it was last taken
+    pub fn take(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
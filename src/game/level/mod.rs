@@ -1,31 +1,34 @@
 // A bunch of tiles, doors, etc...
 
-use std::{collections::{HashMap, HashSet}, f32::consts::PI};
+use std::{collections::{HashMap, HashSet}, f32::consts::PI, rc::Rc};
 
 use macroquad::{color::{Color, WHITE}, math::{vec2, Rect, Vec2}, shapes::draw_line};
 use things::{Door, DoorKind, Sign};
-use tile::{render_tile, LockColor, Tile, TileCollision, TileHit, TileHitKind, TileRenderLayer, TileTextureConnection, TileTextureConnectionKind};
+use tile::{render_tile, LockColor, Tile, TileCollision, TileDataManager, TileHit, TileHitKind, TileRenderLayer, TileTextureConnection, TileTextureConnectionKind};
 
-use crate::{level_pack_data::LevelPosition, resources::Resources, text_renderer::{render_text, Align, Font}};
+use crate::{level_pack_data::LevelPosition, resources::Resources, text_renderer::{render_text, Align, Font}, util::draw_rect, VIEW_SIZE};
 
-use super::{entity::EntityKind, scene::particles::Particles};
+use super::{entity::{props::EntityProps, EntityKind}, events::{GameEvent, GameEvents}, scene::particles::Particles};
 
 pub mod tile;
 pub mod things;
 
+#[derive(Clone)]
 pub struct BumpedTile {
     tile: Tile,
     index: usize,
     timer: f32,
 }
 
+#[derive(Clone)]
 pub struct Level {
     bg_col: Color,
 
     width: usize,
     height: usize,
-    tiles: Vec<Tile>,
-    tiles_bg: Vec<Tile>,
+    // These are shared with any snapshots of the level, and only copied when they're changed
+    tiles: Rc<Vec<Tile>>,
+    tiles_bg: Rc<Vec<Tile>>,
     bumped_tiles: Vec<BumpedTile>,
 
     new_on_off_state: Option<bool>,
@@ -42,7 +45,10 @@ pub struct Level {
     doors: Vec<Door>,
 
     // entity start points, kinds, and if they should be respawned 
-    entity_spawns: HashMap<LevelPosition, EntityKind>,
+    entity_spawns: HashMap<LevelPosition, (EntityKind, EntityProps)>,
+
+    // Everything that can happen in a level has a &mut Level, so the events go in here
+    events: GameEvents,
 
     // Rendering shenanigans
     should_update_render_data: bool,
@@ -51,6 +57,19 @@ pub struct Level {
     tiles_background: Vec<TileRenderData>,
 }
 
+// The bits of a level that can change while it's being played, for rewinding
+// Doesn't have any of the render data so it's cheap to make loads of them
+#[derive(Clone)]
+pub struct LevelSnapshot {
+    tiles: Rc<Vec<Tile>>,
+    tiles_bg: Rc<Vec<Tile>>,
+    bumped_tiles: Vec<BumpedTile>,
+    new_on_off_state: Option<bool>,
+    locks_destroyed: HashSet<LockColor>,
+    checkpoint: Option<usize>,
+    signs_read: Vec<bool>,
+}
+
 #[derive(Clone, Copy)]
 pub struct TileRenderData {
     pub tile: Tile,
@@ -66,11 +85,12 @@ pub enum TileDrawKind {
 }
 
 impl Level {
-    pub fn new(bg_col: Color, width: usize, height: usize, tiles: Vec<Tile>, tiles_bg: Vec<Tile>, spawn: Vec2, finish: Vec2, checkpoints: Vec<Vec2>, signs: Vec<Sign>, doors: Vec<Door>, entity_spawns: HashMap<LevelPosition, EntityKind>) -> Self {
+    pub fn new(bg_col: Color, width: usize, height: usize, tiles: Vec<Tile>, tiles_bg: Vec<Tile>, spawn: Vec2, finish: Vec2, checkpoints: Vec<Vec2>, signs: Vec<Sign>, doors: Vec<Door>, entity_spawns: HashMap<LevelPosition, (EntityKind, EntityProps)>) -> Self {
         Self {
             bg_col,
             width, height,
-            tiles, tiles_bg,
+            tiles: Rc::new(tiles),
+            tiles_bg: Rc::new(tiles_bg),
             spawn, finish,
             checkpoints, signs, doors,
             bumped_tiles: vec![],
@@ -78,6 +98,7 @@ impl Level {
             locks_destroyed: HashSet::with_capacity(7),
             checkpoint: None,
             entity_spawns,
+            events: GameEvents::default(),
             should_update_render_data: true,
             tiles_below:      Vec::with_capacity(width*height),
             tiles_above:      Vec::with_capacity(width*height),
@@ -94,6 +115,9 @@ impl Level {
     pub fn height(&self) -> usize {
         self.height
     }
+    pub fn signs(&self) -> &Vec<Sign> {
+        &self.signs
+    }
     pub fn signs_mut(&mut self) -> &mut Vec<Sign> {
         &mut self.signs
     }
@@ -107,12 +131,52 @@ impl Level {
         self.checkpoint
     }
     pub fn set_checkpoint(&mut self, index: usize) {
+        if self.checkpoint != Some(index) {
+            self.events.push(GameEvent::Checkpoint(index));
+        }
         self.checkpoint = Some(index);
     }
-    pub fn entity_spawns(&self) -> &HashMap<LevelPosition, EntityKind> {
+    pub fn entity_spawns(&self) -> &HashMap<LevelPosition, (EntityKind, EntityProps)> {
         &self.entity_spawns
     }
 
+    pub fn add_event(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        self.events.take()
+    }
+
+    pub fn snapshot(&self) -> LevelSnapshot {
+        LevelSnapshot {
+            tiles: Rc::clone(&self.tiles),
+            tiles_bg: Rc::clone(&self.tiles_bg),
+            bumped_tiles: self.bumped_tiles.clone(),
+            new_on_off_state: self.new_on_off_state,
+            locks_destroyed: self.locks_destroyed.clone(),
+            checkpoint: self.checkpoint,
+            signs_read: self.signs.iter().map(|s| s.read()).collect(),
+        }
+    }
+    pub fn restore(&mut self, snapshot: &LevelSnapshot) {
+        // Only redo the render data if the tiles have actually changed
+        if !Rc::ptr_eq(&self.tiles, &snapshot.tiles)
+        || !Rc::ptr_eq(&self.tiles_bg, &snapshot.tiles_bg)
+        || !self.bumped_tiles.is_empty()
+        || !snapshot.bumped_tiles.is_empty() {
+            self.should_update_render_data = true;
+        }
+        self.tiles = Rc::clone(&snapshot.tiles);
+        self.tiles_bg = Rc::clone(&snapshot.tiles_bg);
+        self.bumped_tiles = snapshot.bumped_tiles.clone();
+        self.new_on_off_state = snapshot.new_on_off_state;
+        self.locks_destroyed = snapshot.locks_destroyed.clone();
+        self.checkpoint = snapshot.checkpoint;
+        for (sign, &read) in self.signs.iter_mut().zip(&snapshot.signs_read) {
+            sign.set_read(read);
+        }
+    }
+
     pub fn spawn(&self) -> Vec2 {
         self.spawn
     }
@@ -121,7 +185,7 @@ impl Level {
     }
 
     // Switch blocks - sets the state of all switch tiles in the level and the background
-    fn set_switch_state(&mut self, enabled: bool) {
+    pub fn set_switch_state(&mut self, enabled: bool) {
         self.new_on_off_state = Some(enabled);
     }
 
@@ -131,7 +195,9 @@ impl Level {
 
     // Lock blocks - removes all of the specified colour and spawns particles
     pub fn remove_lock_blocks(&mut self, color: LockColor, particles: &mut Particles) {
-        self.locks_destroyed.insert(color);
+        if self.locks_destroyed.insert(color) {
+            self.events.push(GameEvent::LockOpened(color));
+        }
         let mut check_tile = |i: usize, t: &mut Tile, bg: bool| {
             if *t == Tile::Lock(color) || *t == Tile::LockBlock(color) {
                 if !bg {
@@ -143,10 +209,10 @@ impl Level {
             }
         };
 
-        for (i, t) in self.tiles.iter_mut().enumerate() {
+        for (i, t) in Rc::make_mut(&mut self.tiles).iter_mut().enumerate() {
             check_tile(i, t, false);
         }
-        for (i, t) in self.tiles_bg.iter_mut().enumerate() {
+        for (i, t) in Rc::make_mut(&mut self.tiles_bg).iter_mut().enumerate() {
             check_tile(i, t, true);
         }
     }
@@ -193,8 +259,9 @@ impl Level {
                 if self.tiles[index] == Tile::StoneBlock {
                     particles.add_stone_block(pos.floor() * 16.0 + 8.0);
                 }
-                self.tiles[index] = *new;
+                Rc::make_mut(&mut self.tiles)[index] = *new;
                 self.should_update_render_data = true;
+                self.events.push(GameEvent::TileBroken);
             }
         }
     }
@@ -204,7 +271,7 @@ impl Level {
             let pos = Level::tile_pos(bumped_tile.index, self.width) - vec2(0.0, (bumped_tile.timer * PI * 0.9).sin()) * 8.0;
 
             let render_data = TileRenderData { draw_kind: TileDrawKind::Single(0), tile: bumped_tile.tile, pos};
-            render_tile(&render_data, camera_pos, TileRenderLayer::Foreground(false), resources);
+            render_tile(&render_data, camera_pos, TileRenderLayer::Foreground(1.0), resources);
         }
     }
 
@@ -238,7 +305,7 @@ impl Level {
 
     pub fn fixed_update(&mut self) {
         if let Some(enabled) = self.new_on_off_state.take() {
-            for t in self.tiles.iter_mut().chain(self.tiles_bg.iter_mut()) {
+            for t in Rc::make_mut(&mut self.tiles).iter_mut().chain(Rc::make_mut(&mut self.tiles_bg).iter_mut()) {
                 match t {
                     Tile::Switch(state) |
                     Tile::SwitchBlockOn(state)  => *state =  enabled,
@@ -252,20 +319,20 @@ impl Level {
     // If we should update the tiles, do it!
     pub fn update_if_should(&mut self, resources: &Resources) {
         if self.should_update_render_data {
-            Level::update_tile_render_data(&mut self.tiles_below, &mut self.tiles_above, &mut self.tiles_background, Some(&self.bumped_tiles), &self.tiles, &self.tiles_bg, self.width, self.height, resources);
+            Level::update_tile_render_data(&mut self.tiles_below, &mut self.tiles_above, &mut self.tiles_background, Some(&self.bumped_tiles), &self.tiles, &self.tiles_bg, self.width, self.height, resources.tile_data_manager());
             self.should_update_render_data = false;
         }
     }
 
     pub fn render_bg(&self, camera_pos: Vec2, resources: &Resources) {
-        Level::render_tiles(&self.tiles_background, camera_pos, TileRenderLayer::Background, resources);
+        Level::render_tiles(&self.tiles_background, camera_pos, TileRenderLayer::Background(1.0), resources);
     }
 
     pub fn render_below(&self, camera_pos: Vec2, resources: &Resources) {
-        Level::render_tiles(&self.tiles_below, camera_pos, TileRenderLayer::Foreground(false), resources);
+        Level::render_tiles(&self.tiles_below, camera_pos, TileRenderLayer::Foreground(1.0), resources);
         
         for s in &self.signs {
-            Level::render_sign(s.pos(), camera_pos, resources);
+            Level::render_sign(s.pos(), camera_pos, WHITE, resources);
         }
         Level::render_checkpoints_sign(&self.checkpoints, self.checkpoint, camera_pos, resources);
     }
@@ -273,13 +340,14 @@ impl Level {
     pub fn render_above(&self, camera_pos: Vec2, resources: &Resources, debug: bool) {
         Level::render_finish(self.finish, camera_pos, resources);
         Level::render_checkpoints_dirt(&self.checkpoints, camera_pos, resources);
-        Level::render_tiles(&self.tiles_above, camera_pos, TileRenderLayer::Foreground(false), resources);
+        Level::render_tiles(&self.tiles_above, camera_pos, TileRenderLayer::Foreground(1.0), resources);
         Level::render_sign_read_alerts(&self.signs, camera_pos, resources);
         if debug {
             for door in &self.doors {
-                Level::render_door_debug(door.kind(), door.pos(), door.dest(), camera_pos, resources);
+                Level::render_door_debug(door.kind(), door.pos(), door.dest(), camera_pos, WHITE, resources);
             }
             Level::render_spawn_finish_debug(self.spawn, self.finish, camera_pos, resources);
+            Level::render_collision_debug(&self.tiles, self.width, camera_pos, resources);
         }
     }
 
@@ -312,8 +380,25 @@ impl Level {
         }
     }
 
+    // Colors each tile by its collision, for debugging / in the editor
+    pub fn render_collision_debug(tiles: &[Tile], width: usize, camera_pos: Vec2, resources: &Resources) {
+        // Only bother with the tiles that are on screen
+        let min = (camera_pos / 16.0).floor().max(Vec2::ZERO);
+        let max = ((camera_pos + VIEW_SIZE) / 16.0).ceil();
+        let height = tiles.len() / width.max(1);
+
+        for y in min.y as usize..(max.y as usize).min(height) {
+            for x in min.x as usize..(max.x as usize).min(width) {
+                let Some(color) = resources.tile_data(tiles[y * width + x]).collision().debug_color() else {
+                    continue;
+                };
+                draw_rect(Rect::new(x as f32 * 16.0 - camera_pos.x, y as f32 * 16.0 - camera_pos.y, 16.0, 16.0), color);
+            }
+        }
+    }
+
     // Renders doors for debugging / in the editor
-    pub fn render_door_debug(kind: DoorKind, pos: Vec2, dest: Vec2, camera_pos: Vec2, resources: &Resources) {
+    pub fn render_door_debug(kind: DoorKind, pos: Vec2, dest: Vec2, camera_pos: Vec2, color: Color, resources: &Resources) {
         let pos  = pos  - camera_pos;
         let dest = dest - camera_pos;
 
@@ -328,13 +413,13 @@ impl Level {
             Rect::new(224.0, tex_y, 16.0, 16.0)
         );
 
-        resources.draw_rect(pos,  pos_tex,  false, false, WHITE, resources.entity_atlas());
-        resources.draw_rect(dest, dest_tex, false, false, WHITE, resources.entity_atlas());
-        draw_line(pos.x + 8.0, pos.y + 8.0, dest.x + 8.0, dest.y + 8.0, 1.0, line_col);
+        resources.draw_rect(pos,  pos_tex,  false, false, color, resources.entity_atlas());
+        resources.draw_rect(dest, dest_tex, false, false, color, resources.entity_atlas());
+        draw_line(pos.x + 8.0, pos.y + 8.0, dest.x + 8.0, dest.y + 8.0, 1.0, Color { a: line_col.a * color.a, ..line_col });
     }
     // Renders a sign
-    pub fn render_sign(pos: Vec2, camera_pos: Vec2, resources: &Resources) {
-        resources.draw_rect(pos - camera_pos, Rect::new(240.0, 0.0, 16.0, 16.0), false, false, WHITE, resources.entity_atlas());
+    pub fn render_sign(pos: Vec2, camera_pos: Vec2, color: Color, resources: &Resources) {
+        resources.draw_rect(pos - camera_pos, Rect::new(240.0, 0.0, 16.0, 16.0), false, false, color, resources.entity_atlas());
     }
     // Renders the alerts above signs if they haven't been read
     pub fn render_sign_read_alerts(signs: &Vec<Sign>, camera_pos: Vec2, resources: &Resources) {
@@ -374,7 +459,7 @@ impl Level {
         tiles_bg: &Vec<Tile>,
         width: usize,
         height: usize,
-        resources: &Resources
+        tile_data_manager: &TileDataManager
     ) {
         tiles_below.clear();
         tiles_above.clear();
@@ -451,7 +536,7 @@ impl Level {
 
         for (i, &tile) in tiles.iter().enumerate() {
             // Don't render the tile if it doesn't have a texture
-            let texture = match resources.tile_data(tile).texture() {
+            let texture = match tile_data_manager.data(tile).texture() {
                 Some(t) => t,
                 None => continue,
             };
@@ -481,7 +566,7 @@ impl Level {
         // BUT I DON'T CARE GRRAAAAHHH!!!!
         for (i, &tile) in tiles_bg.iter().enumerate() {
             // Don't render the tile if it doesn't have a texture
-            let texture = match resources.tile_data(tile).texture() {
+            let texture = match tile_data_manager.data(tile).texture() {
                 Some(t) => t,
                 None => continue,
             };
@@ -12,6 +12,34 @@ pub const RAINBOW_LOCK_FRAME_DUR: f64 = 0.1;
 pub enum TileDir {
     Top, Bottom, Left, Right
 }
+impl TileDir {
+    // For mirroring and rotating things in the editor
+    pub fn flipped_h(self) -> Self {
+        match self {
+            Self::Left  => Self::Right,
+            Self::Right => Self::Left,
+            d => d,
+        }
+    }
+    pub fn flipped_v(self) -> Self {
+        match self {
+            Self::Top    => Self::Bottom,
+            Self::Bottom => Self::Top,
+            d => d,
+        }
+    }
+    pub fn rotated_cw(self) -> Self {
+        match self {
+            Self::Top    => Self::Right,
+            Self::Right  => Self::Bottom,
+            Self::Bottom => Self::Left,
+            Self::Left   => Self::Top,
+        }
+    }
+    pub fn rotated_ccw(self) -> Self {
+        self.rotated_cw().rotated_cw().rotated_cw()
+    }
+}
 
 #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
 pub enum LockColor {
@@ -517,12 +545,46 @@ impl TileCollision {
     pub fn is_ladder(&self) -> bool {
         matches!(self, Self::Ladder)
     }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::None      => "No collision",
+            Self::Platform  => "Platform",
+            Self::Solid {..} => "Solid",
+            Self::Ladder    => "Ladder",
+        }
+    }
+    // The color for the collision debug overlay
+    // Solid tiles that break or bump are colored differently so they're easy to spot
+    pub fn debug_color(&self) -> Option<Color> {
+        match self {
+            Self::None     => None,
+            Self::Platform => Some(color_u8!(  0, 255,   0, 100)),
+            Self::Ladder   => Some(color_u8!(255,   0, 255, 100)),
+            Self::Solid { hit_soft: TileHit::Replace {..}, .. } |
+            Self::Solid { hit_hard: TileHit::Replace {..}, .. } => Some(color_u8!(255, 200,   0, 100)),
+            Self::Solid { hit_soft: TileHit::Bump, .. } |
+            Self::Solid { hit_hard: TileHit::Bump, .. } => Some(color_u8!(  0, 255, 255, 100)),
+            Self::Solid {..} => Some(color_u8!(  0,  80, 255, 100)),
+        }
+    }
+}
+
+impl TileHit {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::None => "Nothing",
+            Self::Bump => "Bumps",
+            Self::Replace {..} => "Breaks",
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
 pub enum TileRenderLayer {
-    Foreground(bool), // Transparent or not
-    Background,
+    // How see-through the tile is, only the editor draws them less than fully opaque
+    Foreground(f32),
+    Background(f32),
 }
 
 // Rendering a tile
@@ -565,9 +627,8 @@ pub fn render_tile(render_data: &TileRenderData, camera_pos: Vec2, render_layer:
 
     // If it's a background tile we want to draw it darker
     let color = match render_layer {
-        TileRenderLayer::Foreground(false) => WHITE,
-        TileRenderLayer::Foreground(true)  => Color::from_rgba(255, 255, 255,  64),
-        TileRenderLayer::Background        => Color::from_rgba(150, 150, 150, 255),
+        TileRenderLayer::Foreground(a) => Color { a, ..WHITE },
+        TileRenderLayer::Background(a) => Color { a, ..Color::from_rgba(150, 150, 150, 255) },
     };
 
     // Draws a tile that's a single texture
@@ -1,15 +1,19 @@
 // A bunch of levels to be played, the global chip counter, etc.
 // Loaded from a level pack
 
-use std::time::Instant;
+use std::{collections::HashSet, time::Instant};
 
 use macroquad::{color::BLACK, input::{is_key_pressed, KeyCode}, math::Vec2, window::clear_background};
-use pause_menu::PauseMenu;
+use achievements::{Achievement, Achievements};
+use death_map::DeathMap;
+use difficulty::Difficulty;
+use events::GameEvent;
+use pause_menu::{PauseAction, PauseMenu};
 use player::{FeetPowerup, HeadPowerup};
-use scene::Scene;
+use scene::{Scene, KEY_QUICKLOAD, KEY_QUICKSAVE};
 use transition::{Transition, TransitionKind};
 
-use crate::{level_pack_data::LevelPackData, menu::Menu, resources::Resources, ui::Ui, GameState};
+use crate::{level_pack_data::LevelPackData, menu::Menu, resources::Resources, ui::{toast::{ToastKind, ToastManager}, Ui}, GameState};
 
 pub mod pause_menu;
 pub mod transition; // based and redpilled ?
@@ -18,6 +22,10 @@ pub mod scene;
 pub mod collision;
 pub mod entity;
 pub mod player;
+pub mod death_map;
+pub mod events;
+pub mod achievements;
+pub mod difficulty;
 
 #[derive(PartialEq, Eq, Debug)]
 enum TransitionAction {
@@ -31,6 +39,7 @@ pub struct Game {
     pause_menu: PauseMenu,
 
     level_pack: LevelPackData,
+    difficulty: Difficulty,
     current_level: usize,
 
     level_name: String,
@@ -39,6 +48,16 @@ pub struct Game {
     world_num: usize,
     next_powerups: (Option<HeadPowerup>, Option<FeetPowerup>),
     checkpoint: Option<usize>,
+    // Practice mode, toggled in the pause menu
+    // Lives aren't used, and nothing that happens counts towards deaths, chips, achievements, etc...
+    practicing: bool,
+    // If practice mode has been used at all this play through, so the final time doesn't count
+    practiced: bool,
+    practice_checkpoint: Option<Vec2>,
+    // The scene when F5 was pressed while practicing, for the current level
+    quicksave: Option<Scene>,
+    // If any assists have been turned on this play through
+    assisted: bool,
 
     // player types
     scene: Option<Scene>,
@@ -47,10 +66,19 @@ pub struct Game {
     deaths: usize,
     gameovers: usize,
     begin: Instant,
+    // Where the player has died in each level, for the heatmap in the editor
+    death_map: DeathMap,
+
+    achievements: Achievements,
+    // (level, sign index) of every sign read this play through
+    signs_read: HashSet<(usize, usize)>,
+    // Levels finished with every chip collected
+    all_chips_levels: HashSet<usize>,
 }
 
 impl Game {
-    pub fn new(level_pack: LevelPackData) -> Self {
+    pub fn new(level_pack: LevelPackData, difficulty: Difficulty) -> Self {
+        let death_map = DeathMap::load(level_pack.file_name());
         Self {
             transition: Transition::new(&level_pack),
             transition_action: None,
@@ -58,6 +86,7 @@ impl Game {
             pause_menu: PauseMenu::default(),
 
             level_pack,
+            difficulty,
             current_level: 0,
 
             level_name: String::from("you'll never see this"),
@@ -66,27 +95,82 @@ impl Game {
             world_num: 69,
             next_powerups: (None, None),
             checkpoint: None,
+            practicing: false,
+            practiced: false,
+            practice_checkpoint: None,
+            quicksave: None,
+            assisted: false,
 
             scene: None,
-            lives: 3,
+            lives: difficulty.starting_lives(),
             chips: 0,
             deaths: 0,
             gameovers: 0,
             begin: Instant::now(),
+            death_map,
+
+            achievements: Achievements::load(),
+            signs_read: HashSet::new(),
+            all_chips_levels: HashSet::new(),
+        }
+    }
+
+    // Lives can be made infinite by the difficulty or the assist in the settings
+    fn infinite_lives(&self) -> bool {
+        self.difficulty.infinite_lives() || self.pause_menu.settings().infinite_lives()
+    }
+
+    // Restarting goes the same way as respawning after dying, it just costs however many lives the settings say
+    fn restart(&mut self, from_checkpoint: bool) {
+        if !from_checkpoint {
+            self.checkpoint = None;
+            self.practice_checkpoint = None;
+        }
+        if !self.practicing && !self.infinite_lives() {
+            self.lives = self.lives.saturating_sub(self.pause_menu.settings().restart_life_cost());
+        }
+        self.transition_action = Some(TransitionAction::Intro);
+    }
+
+    fn update_sign_achievement(&mut self, toast_manager: &mut ToastManager) {
+        let total: usize = self.level_pack.levels().iter().map(|l| l.sign_count()).sum();
+        if total != 0 && self.signs_read.len() == total {
+            self.achievements.unlock(Achievement::Bookworm, toast_manager);
+        }
+    }
+
+    // Checks if every level in the current level's world has been finished with all of its chips
+    fn update_chip_achievement(&mut self, toast_manager: &mut ToastManager) {
+        let Some(world) = self.level_pack.levels().get(self.current_level).map(|l| l.world()) else {
+            return;
+        };
+        let mut levels = self.level_pack.levels().iter().enumerate().filter(|(_, l)| l.world() == world);
+        // A world without any chips doesn't count!
+        let has_chips = levels.clone().any(|(_, l)| l.chip_count() != 0);
+        if has_chips && levels.all(|(i, _)| self.all_chips_levels.contains(&i)) {
+            self.achievements.unlock(Achievement::ChipCollector, toast_manager);
         }
     }
 }
 
 impl GameState for Game {
-    fn update(&mut self, deltatime: f32, ui: &mut Ui, resources: &mut Resources, next_state: &mut Option<Box<dyn GameState>>) {
+    fn update(&mut self, deltatime: f32, ui: &mut Ui, resources: &mut Resources, toast_manager: &mut ToastManager, next_state: &mut Option<Box<dyn GameState>>) {
         if is_key_pressed(KeyCode::Escape) && !self.pause_menu.on_submenu() {
             self.pause_menu.set_active(!self.pause_menu.active());
         }
         resources.set_anim_timer_update(!self.pause_menu.active());
         if self.pause_menu.active() {
-            let exit = self.pause_menu.update(deltatime, ui);
-            if exit {
-                *next_state = Some(Box::new(Menu::new(Some(self.level_pack.file_name().clone()))));
+            // Restarting can't happen mid-transition, or if it'd take away the last life (unless it's free)
+            let cost = self.pause_menu.settings().restart_life_cost();
+            let can_afford = self.practicing || self.infinite_lives() || cost == 0 || self.lives > cost;
+            let playing = self.transition.can_update() && self.scene.as_ref().is_some_and(|s| !s.dead() && !s.completed());
+            self.pause_menu.set_restart_allowed(can_afford && playing);
+
+            match self.pause_menu.update(deltatime, ui) {
+                Some(PauseAction::Exit) => *next_state = Some(Box::new(Menu::new(Some(self.level_pack.file_name().clone()), toast_manager))),
+                Some(PauseAction::RestartCheckpoint) => self.restart(true),
+                Some(PauseAction::RestartLevel) => self.restart(false),
+                None => {}
             }
             return;
         }
@@ -101,9 +185,12 @@ impl GameState for Game {
                 TransitionKind::Death(_)  => self.transition_action = Some(TransitionAction::Intro),
                 _ => self.transition_action = None,
             };
-            if matches!(self.transition.kind(), TransitionKind::Death(_)) {
+            // Practice mode could've been turned off during the transition, so lives might already be 0
+            if matches!(self.transition.kind(), TransitionKind::Death(_)) && !self.practicing {
                 self.deaths += 1;
-                self.lives -= 1;
+                if !self.infinite_lives() {
+                    self.lives = self.lives.saturating_sub(1);
+                }
             }
             if matches!(self.transition.kind(), TransitionKind::GameOver(_)) {
                 // Go back to the first level in the world
@@ -116,7 +203,7 @@ impl GameState for Game {
                 }
                 self.deaths += 1;
                 self.gameovers += 1;
-                self.lives = 3;
+                self.lives = self.difficulty.starting_lives();
                 self.chips = 0;
             }
             self.transition.set_none();
@@ -129,9 +216,14 @@ impl GameState for Game {
                     // Load the level and begin the transition
                     if let Some(level_data) = self.level_pack.levels().get(self.current_level) {
                         let mut scene = Scene::new(level_data, self.checkpoint, self.next_powerups.0, self.next_powerups.1);
+                        scene.set_practicing(self.practicing);
+                        scene.set_assists(self.pause_menu.settings().invincible(), self.pause_menu.settings().game_speed());
+                        scene.set_hurt_kills(self.difficulty.hurt_kills());
+                        if let Some(pos) = self.practice_checkpoint {
+                            scene.set_practice_checkpoint(pos);
+                        }
                         // Update the scene so we can load all the entities and stuff
-                        // kinda hacky passing &mut 1... idk
-                        scene.update(&mut 0, &mut 0, 0.0, resources);
+                        scene.update(0.0, resources);
                         self.scene = Some(scene);
 
                         self.level_num = 1 + self.current_level - self.level_pack.levels()
@@ -154,7 +246,8 @@ impl GameState for Game {
                             level_data.world(),
                             self.next_powerups.0.take(),
                             self.next_powerups.1.take(),
-                            self.lives
+                            self.lives,
+                            self.difficulty,
                         );
                     }
                     // Or if we've beaten all the levels, show the end screen!
@@ -169,9 +262,17 @@ impl GameState for Game {
                         let seconds = (total_millis % 60_000) / 1000;
                         let centis = (total_millis % 1000) / 10;
                     
-                        let timer = format!("{:02}:{:02}:{:02}.{:02}", hours, minutes, seconds, centis);
+                        let timer = match self.practiced {
+                            true  => String::from("Practice"),
+                            false => format!("{:02}:{:02}:{:02}.{:02}", hours, minutes, seconds, centis),
+                        };
+
+                        // Casual has infinite lives, so there's no way to get a game over
+                        if self.gameovers == 0 && !self.practiced && !self.assisted && !self.difficulty.infinite_lives() {
+                            self.achievements.unlock(Achievement::NoGameOver, toast_manager);
+                        }
 
-                        self.transition.begin_pack_finish(self.level_pack.name().clone(), self.level_pack.author().clone(), head, feet, self.chips, self.deaths, self.gameovers, timer);
+                        self.transition.begin_pack_finish(self.level_pack.name().clone(), self.level_pack.author().clone(), head, feet, self.chips, self.deaths, self.gameovers, timer, self.assisted);
                     }
                 }
                 TransitionAction::Finish => {
@@ -180,9 +281,15 @@ impl GameState for Game {
                         // Should never happen, but im not gonna unwrap now, am i?!
                         None => (Vec2::ZERO, None, None),
                     };
+                    if self.scene.as_ref().is_some_and(|s| s.chips_left() == 0) && !self.practicing {
+                        self.all_chips_levels.insert(self.current_level);
+                        self.update_chip_achievement(toast_manager);
+                    }
                     self.current_level += 1;
                     self.next_powerups = (head, feet);
                     self.checkpoint = None;
+                    self.practice_checkpoint = None;
+                    self.quicksave = None;
                     self.transition.begin_finish(center);
                     
                 }
@@ -199,6 +306,8 @@ impl GameState for Game {
                         None => Vec2::ZERO,
                     };
                     self.checkpoint = None;
+                    self.practice_checkpoint = None;
+                    self.quicksave = None;
                     self.transition.begin_game_over(center);
                 }
             }
@@ -206,7 +315,7 @@ impl GameState for Game {
 
         if matches!(self.transition.kind(), TransitionKind::PackFinish(..)) {
             if is_key_pressed(KeyCode::Space) {
-                *next_state = Some(Box::new(Menu::new(Some(self.level_pack.file_name().clone()))));
+                *next_state = Some(Box::new(Menu::new(Some(self.level_pack.file_name().clone()), toast_manager)));
                 return;
             }
         }
@@ -217,21 +326,73 @@ impl GameState for Game {
             return;
         }
         resources.set_anim_timer_update(true);
+        let signs_before = self.signs_read.len();
+        self.practicing = self.pause_menu.practice();
+        self.practiced |= self.practicing;
+        if !self.practicing {
+            self.quicksave = None;
+        }
+        let settings = *self.pause_menu.settings();
+        self.assisted |= settings.assists_enabled();
         if let Some(scene) = &mut self.scene {
-            scene.update(&mut self.chips, &mut self.lives, deltatime, resources);
-            self.checkpoint = scene.checkpoint();
+            scene.set_practicing(self.practicing);
+            scene.set_assists(settings.invincible(), settings.game_speed());
+            scene.update(deltatime, resources);
+            for event in scene.take_events() {
+                if event == GameEvent::PracticeCheckpoint {
+                    toast_manager.add_toast(String::from("Checkpoint placed!"), ToastKind::Info);
+                }
+                if self.practicing {
+                    continue;
+                }
+                match event {
+                    GameEvent::ChipCollected => self.chips += 1,
+                    GameEvent::LifeCollected if self.difficulty.lives_collectable() => self.lives += 1,
+                    _ => {}
+                }
+                self.achievements.handle_event(event, toast_manager);
+            }
+            if !self.practicing {
+                self.signs_read.extend(scene.read_signs().map(|i| (self.current_level, i)));
+            }
+            // Quicksaving / quickloading while practicing, chips and lives don't change when practicing so only the scene's needed
+            if self.practicing && is_key_pressed(KEY_QUICKSAVE) {
+                self.quicksave = Some(scene.clone());
+                toast_manager.add_toast(String::from("Quicksaved"), ToastKind::Info);
+            }
+            if self.practicing && is_key_pressed(KEY_QUICKLOAD) {
+                match &self.quicksave {
+                    Some(saved) => {
+                        *scene = saved.clone();
+                        toast_manager.add_toast(String::from("Quickloaded"), ToastKind::Info);
+                    }
+                    None => toast_manager.add_toast(String::from("No quicksave! (F5)"), ToastKind::Warning),
+                }
+            }
+            self.checkpoint = scene.checkpoint().filter(|_| self.difficulty.checkpoints());
+            self.practice_checkpoint = scene.practice_checkpoint();
+            // Not being able to save the deaths isn't a big deal, but it's still worth knowing about
+            if let Some(death) = scene.take_death().filter(|_| !self.practicing) {
+                self.death_map.record(self.current_level, death);
+                if self.death_map.save().is_err() {
+                    toast_manager.add_toast(String::from("Couldn't save deaths!"), ToastKind::Warning);
+                }
+            }
 
             self.transition_action = match (scene.completed(), scene.dead(), self.lives) {
                 // Finishing level
                 (true, _, _) => Some(TransitionAction::Finish),
                 // Game over
-                (_, true, 0) => Some(TransitionAction::GameOver),
+                (_, true, 0) if !self.practicing && !self.infinite_lives() => Some(TransitionAction::GameOver),
                 // Respawning
                 (_, true, _) => Some(TransitionAction::Death),
                 // Nothing
                 _=> None,
             };
         }
+        if self.signs_read.len() != signs_before {
+            self.update_sign_achievement(toast_manager);
+        }
     }
 
     fn draw(&self, _ui: &Ui, resources: &Resources, debug: bool) {
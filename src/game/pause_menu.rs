@@ -2,19 +2,32 @@ use std::f32::consts::PI;
 
 use macroquad::{color::Color, color_u8, input::{is_key_pressed, KeyCode}, math::{vec2, Rect, Vec2}, shapes::draw_rectangle};
 
-use crate::{menu::{fancy_cute_rainbow_text, submenu::{Submenu, SubmenuState}}, resources::Resources, ui::{button::Button, Ui}, VIEW_SIZE};
+use crate::{menu::{fancy_cute_rainbow_text, submenu::{Submenu, SubmenuState}}, resources::Resources, settings::Settings, ui::{button::Button, Ui}, VIEW_SIZE};
 
 const BG_COL: Color = color_u8!(255, 255, 255, 150);
-const BUTTONS_WIDTH: f32 = 150.0;
-const BUTTONS_GAP: f32 = 16.0 + 4.0;
-const BUTTONS_BEGIN: Vec2 = vec2((VIEW_SIZE.x - BUTTONS_WIDTH) / 2.0, 90.0);
+const BUTTONS_WIDTH: f32 = 190.0;
+const BUTTONS_GAP: f32 = 16.0 + 2.0;
+const BUTTONS_BEGIN: Vec2 = vec2((VIEW_SIZE.x - BUTTONS_WIDTH) / 2.0, 66.0);
+
+// Things the pause menu needs the game to do
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PauseAction {
+    RestartCheckpoint,
+    RestartLevel,
+    Exit,
+}
 
 pub struct PauseMenu {
     active: bool,
     logo_timer: f32,
     submenu: Submenu,
+    practice: bool,
     // buttons
     resume:  Button,
+    practice_toggle: Button,
+    restart_checkpoint: Button,
+    restart_level: Button,
+    settings: Button,
     help:    Button,
     credits: Button,
     exit:    Button,
@@ -26,10 +39,15 @@ impl Default for PauseMenu {
             active: false,
             logo_timer: 0.0,
             submenu: Submenu::default(),
+            practice: false,
             resume:  Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 0.0, BUTTONS_WIDTH, 16.0), Some(String::from("Resume")), None),
-            help:    Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 1.0, BUTTONS_WIDTH, 16.0), Some(String::from("How to play")), None),
-            credits: Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 2.0, BUTTONS_WIDTH, 16.0), Some(String::from("Credits")), None),
-            exit:    Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 3.0, BUTTONS_WIDTH, 16.0), Some(String::from("Exit")), None),
+            practice_toggle: Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 1.0, BUTTONS_WIDTH, 16.0), Some(String::from("Practice: Off")), Some(String::from("R to rewind, C for checkpoints"))),
+            restart_checkpoint: Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 2.0, BUTTONS_WIDTH, 16.0), Some(String::from("Restart from checkpoint")), None),
+            restart_level: Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 3.0, BUTTONS_WIDTH, 16.0), Some(String::from("Restart level")), None),
+            settings: Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 4.0, BUTTONS_WIDTH, 16.0), Some(String::from("Settings")), None),
+            help:    Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 5.0, BUTTONS_WIDTH, 16.0), Some(String::from("How to play")), None),
+            credits: Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 6.0, BUTTONS_WIDTH, 16.0), Some(String::from("Credits")), None),
+            exit:    Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 7.0, BUTTONS_WIDTH, 16.0), Some(String::from("Exit")), None),
         }
     }
 }
@@ -42,12 +60,23 @@ impl PauseMenu {
         self.active = active;
     }
 
+    pub fn practice(&self) -> bool {
+        self.practice
+    }
+    pub fn settings(&self) -> &Settings {
+        self.submenu.settings()
+    }
+    // e.g. if restarting would cost the last life
+    pub fn set_restart_allowed(&mut self, allowed: bool) {
+        self.restart_checkpoint.set_disabled(!allowed);
+        self.restart_level.set_disabled(!allowed);
+    }
+
     pub fn on_submenu(&self) -> bool {
         self.submenu.is_some()
     }
 
-    // Returns true if we should exit back to the menu
-    pub fn update(&mut self, deltatime: f32, ui: &mut Ui) -> bool {
+    pub fn update(&mut self, deltatime: f32, ui: &mut Ui) -> Option<PauseAction> {
         self.logo_timer = (self.logo_timer + deltatime).rem_euclid(PI);
 
         if self.submenu.is_some() {
@@ -55,18 +84,40 @@ impl PauseMenu {
                 self.submenu.set_submenu_state(SubmenuState::None);
             } else {
                 self.submenu.update(ui);
-                return false;
+                return None;
             }
         }
 
         self.resume.update(ui);
+        self.practice_toggle.update(ui);
+        self.restart_checkpoint.update(ui);
+        self.restart_level.update(ui);
+        self.settings.update(ui);
         self.help.update(ui);
         self.credits.update(ui);
         self.exit.update(ui);
 
         if self.resume.released() {
             self.active = false;
-            return false;
+            return None;
+        }
+        if self.practice_toggle.released() {
+            self.practice = !self.practice;
+            self.practice_toggle.set_label(match self.practice {
+                true  => "Practice: On",
+                false => "Practice: Off",
+            });
+        }
+        if self.restart_checkpoint.released() {
+            self.active = false;
+            return Some(PauseAction::RestartCheckpoint);
+        }
+        if self.restart_level.released() {
+            self.active = false;
+            return Some(PauseAction::RestartLevel);
+        }
+        if self.settings.released() {
+            self.submenu.set_submenu_state(SubmenuState::Settings);
         }
         if self.help.released() {
             self.submenu.set_submenu_state(SubmenuState::Help);
@@ -75,10 +126,10 @@ impl PauseMenu {
             self.submenu.set_submenu_state(SubmenuState::Credits);
         }
         if self.exit.released() {
-            return true;
+            return Some(PauseAction::Exit);
         }
 
-        false
+        None
     }
 
     pub fn draw(&self, resources: &Resources) {
@@ -91,6 +142,10 @@ impl PauseMenu {
 
         fancy_cute_rainbow_text("Paused", vec2(VIEW_SIZE.x / 2.0, 38.0), self.logo_timer, resources);
         self.resume.draw(resources);
+        self.practice_toggle.draw(resources);
+        self.restart_checkpoint.draw(resources);
+        self.restart_level.draw(resources);
+        self.settings.draw(resources);
         self.help.draw(resources);
         self.credits.draw(resources);
         self.exit.draw(resources);
@@ -4,7 +4,7 @@ use macroquad::{color::{Color, BLUE, GREEN, RED, WHITE, YELLOW}, input::{is_key_
 
 use crate::{game::collision::spike_check, resources::Resources, util::{approach_target, draw_rect_lines}};
 
-use super::{collision::{collision_bottom, collision_left, collision_right, collision_top, lava_check, solid_on_off_check}, entity::{Entity, EntityKind, Id}, level::{things::DoorKind, tile::{TileCollision, TileDir, TileHitKind}, Level}, scene::{camera::Camera, entity_spawner::EntitySpawner, fader::Fader, particles::Particles, sign_display::SignDisplay, GRAVITY, MAX_FALL_SPEED}};
+use super::{collision::{collision_bottom, collision_left, collision_right, collision_top, lava_check, solid_on_off_check}, death_map::{Death, DeathCause}, events::GameEvent, entity::{Entity, EntityKind, Id}, level::{things::DoorKind, tile::{TileCollision, TileDir, TileHitKind}, Level}, scene::{camera::Camera, entity_spawner::EntitySpawner, fader::Fader, particles::Particles, sign_display::SignDisplay, GRAVITY, MAX_FALL_SPEED}};
 
 // Collision points
 const HEAD:    Vec2 = vec2( 8.0,  0.0);
@@ -16,7 +16,18 @@ const FOOT_L:  Vec2 = vec2( 5.0, 16.0);
 const FOOT_R:  Vec2 = vec2(10.0, 16.0);
 
 const HOLD_CHECK: Vec2 = vec2(8.0, -15.0);
+
+// Movement constants, these are public so the editor can work out how far the player can jump
+pub const WALK_SPEED: f32 = 0.6;
+pub const RUN_SPEED_BEG: f32 = 0.7;
+pub const RUN_SPEED_END: f32 = 0.9;
+pub const RUN_TIME_MAX: f32 = 1.5;
+pub const JUMP_VEL: f32 = 1.8;
+pub const AIR_SPEED: f32 = 0.5;
+pub const AIR_APPROACH: f32 = 0.05;
 const CENTER: Vec2 = vec2(8.0, 8.0);
+// How many tiles away an invincible player can be pushed out of a solid on/off block
+const PUSH_OUT_RANGE: i32 = 8;
 
 // Control
 const KEY_LEFT:  KeyCode = KeyCode::A;
@@ -108,11 +119,12 @@ pub enum PlayerPart {
     Feet { powerup: Option<FeetPowerup>, run: bool, ladder: bool },
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Invuln {
     None, Damage(f32), Powerup(PowerupKind, f32),
 }
 
+#[derive(Clone)]
 pub struct Player {
     state: State,
     pos: Vec2,
@@ -143,6 +155,12 @@ pub struct Player {
     nudging_r: bool,
     dead_timer: Option<f32>,
     dead_y: f32,
+    // Where and how the player died, taken by the scene so it can be remembered
+    death: Option<Death>,
+    // The invincibility assist, only falling down a pit can kill the player
+    invincible: bool,
+    // Hard difficulty, getting hurt always kills
+    hurt_kills: bool,
     
     // Constants
     walk_speed: f32,
@@ -181,12 +199,15 @@ impl Player {
             nudging_r: false,
             dead_timer: None,
             dead_y: 0.0,
-
-            walk_speed: 0.6,
-            run_speed_beg: 0.7,
-            run_speed_end: 0.9,
-            run_time_max: 1.5,
-            jump_vel: 1.8,
+            death: None,
+            invincible: false,
+            hurt_kills: false,
+
+            walk_speed: WALK_SPEED,
+            run_speed_beg: RUN_SPEED_BEG,
+            run_speed_end: RUN_SPEED_END,
+            run_time_max: RUN_TIME_MAX,
+            jump_vel: JUMP_VEL,
         }
     }
 
@@ -217,10 +238,22 @@ impl Player {
     pub fn dead(&self) -> bool {
         self.dead_timer.is_some()
     }
+    pub fn death(&self) -> Option<Death> {
+        self.death
+    }
+    pub fn take_death(&mut self) -> Option<Death> {
+        self.death.take()
+    }
     pub fn dead_stop(&self) -> bool {
         self.dead_timer.is_some() && self.vel.y >= 0.0
     }
 
+    pub fn set_invincible(&mut self, invincible: bool) {
+        self.invincible = invincible;
+    }
+    pub fn set_hurt_kills(&mut self, hurt_kills: bool) {
+        self.hurt_kills = hurt_kills;
+    }
     pub fn set_pos(&mut self, pos: Vec2) {
         self.pos = pos;
     }
@@ -307,16 +340,14 @@ impl Player {
     }
 
     fn state_jumping(&mut self, level: &Level, resources: &Resources) {
-        let speed = 0.5;
-        self.target_approach = 0.05;
-        self.air_logic(speed);
+        self.target_approach = AIR_APPROACH;
+        self.air_logic(AIR_SPEED);
         self.allow_climbing(level, resources);
     }
 
     fn state_falling(&mut self, level: &Level, resources: &Resources) {
-        let speed = 0.5;
-        self.target_approach = 0.05;
-        self.air_logic(speed);
+        self.target_approach = AIR_APPROACH;
+        self.air_logic(AIR_SPEED);
         if self.coyote_time < 0.1 && self.vel.x.abs() >= self.run_speed_beg + (self.run_speed_end - self.run_speed_beg) / 2.0 {
             self.allow_jumping(self.jump_vel);
         }
@@ -347,6 +378,23 @@ impl Player {
         }
     }
 
+    // The points that are checked for collision with the level
+    pub fn collision_points() -> [Vec2; 7] {
+        [HEAD, SIDE_LT, SIDE_LB, SIDE_RT, SIDE_RB, FOOT_L, FOOT_R]
+    }
+
+    // The gravity and max fall speed, which depend on the feet powerup and if jump is being held
+    pub fn gravity(feet_powerup: Option<FeetPowerup>, state: State, jump_held: bool) -> (f32, f32) {
+        match (feet_powerup, state, jump_held) {
+            (Some(FeetPowerup::Skirt), State::Falling, true)  => (GRAVITY * 0.9, 0.2),
+            (Some(FeetPowerup::Skirt), State::Falling, false) => (GRAVITY * 0.9, 1.2),
+            (Some(FeetPowerup::MoonShoes), State::Jumping, true) => (GRAVITY * 0.65 * 0.7, MAX_FALL_SPEED),
+            (Some(FeetPowerup::MoonShoes), _, _) => (GRAVITY * 0.65, MAX_FALL_SPEED),
+            (_, State::Jumping, true) => (GRAVITY * 0.7, MAX_FALL_SPEED),
+            _ => (GRAVITY, MAX_FALL_SPEED)
+        }
+    }
+
     // Switching to jumping
     fn allow_jumping(&mut self, velocity: f32) -> bool {
         if is_key_pressed(KEY_JUMP) {
@@ -531,6 +579,7 @@ impl Player {
             if (d.kind() == DoorKind::Door && is_key_pressed(KEY_UP) && matches!(self.state, State::Standing | State::Moving))
             || (d.kind() == DoorKind::Teleporter && !self.prev_in_teleporter) {
                 fader.begin_fade(Some(d.dest()));
+                level.add_event(GameEvent::DoorUsed);
                 break;
             }
             if d.kind() == DoorKind::SeamlessTeleporter && !self.prev_in_teleporter {
@@ -594,14 +643,7 @@ impl Player {
         }
 
         if self.state != State::Climbing {
-            let (gravity, max_fall_speed) = match (self.feet_powerup, self.state, is_key_down(KEY_JUMP)) {
-                (Some(FeetPowerup::Skirt), State::Falling, true)  => (GRAVITY * 0.9, 0.2),
-                (Some(FeetPowerup::Skirt), State::Falling, false) => (GRAVITY * 0.9, 1.2),
-                (Some(FeetPowerup::MoonShoes), State::Jumping, true) => (GRAVITY * 0.65 * 0.7, MAX_FALL_SPEED),
-                (Some(FeetPowerup::MoonShoes), _, _) => (GRAVITY * 0.65, MAX_FALL_SPEED),
-                (_, State::Jumping, true) => (GRAVITY * 0.7, MAX_FALL_SPEED),
-                _ => (GRAVITY, MAX_FALL_SPEED)
-            };
+            let (gravity, max_fall_speed) = Player::gravity(self.feet_powerup, self.state, is_key_down(KEY_JUMP));
             self.vel.y = (self.vel.y + gravity).min(max_fall_speed);
             self.step_anim = (self.step_anim + self.vel.x.abs() / 12.0).rem_euclid(1.0);
         } else {
@@ -683,7 +725,10 @@ impl Player {
         // Solid on/off blocks
         if solid_on_off_check(prev_pos, &[CENTER], level) {
             self.pos = prev_pos;
-            self.kill();
+            match self.invincible {
+                true  => self.push_out_of_solid(level, resources),
+                false => self.kill(DeathCause::Crushed),
+            }
             return;
         }
 
@@ -730,7 +775,10 @@ impl Player {
                     continue;
                 }
                 stomped = true;
+                // Only count it if it actually got stomped, landing on something that can't be hurt is just a bounce
+                // (a successful stomp stops the loop, so it can only be counted once)
                 if e.stomp(self.feet_powerup, relative_dir(e.hitbox().center().x)) {
+                    level.add_event(GameEvent::EnemyStomped(e.kind()));
                     break 'entities;
                 }
             }
@@ -741,28 +789,54 @@ impl Player {
         }
     }
 
-    pub fn hurt(&mut self) {
-        if !matches!(self.invuln, Invuln::None) {
+    pub fn hurt(&mut self, cause: DeathCause) {
+        if !matches!(self.invuln, Invuln::None) || self.invincible {
             return;
         }
         self.invuln = Invuln::Damage(1.5);
-        if self.head_powerup.is_some() {
+        if self.hurt_kills {
+            self.kill(cause);
+        } else if self.head_powerup.is_some() {
             self.head_powerup = None;
         } else if self.feet_powerup.is_some() {
             self.feet_powerup = None;
         } else {
-            self.kill();
+            self.kill(cause);
         }
     }
 
-    pub fn kill(&mut self) {
+    pub fn kill(&mut self, cause: DeathCause) {
+        if self.invincible && cause != DeathCause::Fall {
+            return;
+        }
         self.vel = vec2(0.0, -1.7);
         self.dead_timer = Some(0.0);
         self.dead_y = self.pos.y;
+        self.death = Some(Death { pos: self.pos + CENTER, cause });
         self.head_powerup = None;
         self.feet_powerup = None;
     }
     
+    // Invincible players can't be crushed, so instead of being stuck inside the block they get moved to the nearest free tile
+    fn push_out_of_solid(&mut self, level: &Level, resources: &Resources) {
+        let tile = ((self.pos + CENTER) / 16.0).floor();
+        let free = |t: Vec2| {
+            t.x >= 0.0 && t.x < level.width()  as f32
+            && t.y >= 0.0 && t.y < level.height() as f32
+            && !resources.tile_data(level.tile_at_pos(t * 16.0)).collision().is_solid()
+            && !solid_on_off_check(t * 16.0, &[Vec2::ZERO], level)
+        };
+        let nearest = (-PUSH_OUT_RANGE..=PUSH_OUT_RANGE)
+            .flat_map(|y| (-PUSH_OUT_RANGE..=PUSH_OUT_RANGE).map(move |x| vec2(x as f32, y as f32)))
+            .filter(|o| free(tile + *o))
+            .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+
+        if let Some(o) = nearest {
+            self.pos = (tile + o) * 16.0;
+            self.vel = Vec2::ZERO;
+        }
+    }
+
     pub fn hurt_check(&mut self, entities: &mut Vec<Box<dyn Entity>>, particles: &mut Particles, level: &Level, _resources: &Resources) {
         if self.dead_timer.is_some() {
             return;
@@ -771,11 +845,11 @@ impl Player {
         // These things should kill you even if you're invulnerable!
         // Falling off the map
         if self.pos.y >= level.height() as f32 * 16.0 + 6.0 {
-            self.kill();
+            self.kill(DeathCause::Fall);
         }
         // Lava
         if lava_check(self.pos, &[CENTER], particles, level) {
-            self.kill();
+            self.kill(DeathCause::Lava);
         }
 
         // Update the timer
@@ -788,7 +862,8 @@ impl Player {
             }
             _ => {}
         }
-        if matches!(self.invuln, Invuln::Damage(_)) {
+        // With the invincibility assist there's no point knocking the player back from things that can't hurt them
+        if matches!(self.invuln, Invuln::Damage(_)) || self.invincible {
             return;
         }
 
@@ -804,7 +879,7 @@ impl Player {
             // Hurt the player!!
             for p in [SIDE_LT, SIDE_LB, SIDE_RT, SIDE_RB] {
                 if hurtbox.contains(self.pos + p) {
-                    self.hurt();
+                    self.hurt(DeathCause::from_entity(e.kind()));
                     if self.dead_timer.is_some() {
                         return;
                     }
@@ -818,7 +893,7 @@ impl Player {
 
         // Spikes
         if let Some(dir) = spike_check(self.pos, &[HEAD], &[FOOT_L, FOOT_R], &[SIDE_LB, SIDE_LT], &[SIDE_RB, SIDE_RT], level) {
-            self.hurt();
+            self.hurt(DeathCause::Spikes);
             if self.dead_timer.is_some() {
                 return;
             }
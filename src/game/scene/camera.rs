@@ -17,6 +17,7 @@ const Y_TOP_GRND: f32 = VIEW_SIZE.y / 3.0 * 1.5;
 const Y_TOP:      f32 = VIEW_SIZE.y / 3.0 * 1.0;
 const Y_BOT:      f32 = VIEW_SIZE.y / 3.0 * 2.1;
 
+#[derive(Clone)]
 pub struct Camera {
     center: Vec2,
 
@@ -1,14 +1,17 @@
 use macroquad::math::Vec2;
 
-use crate::{game::entity::{armadillo::Armadillo, cannonball::Cannonball, chip::Chip, crate_entity::Crate, danger_cloud::DangerCloud, explosion::Explosion, fireball::Fireball, flame_jet::FlameJet, frog::Frog, goat::Goat, key::Key, launcher::Launcher, powerup::Powerup, Entity, EntityKind, Id}, level_pack_data::LevelPosition};
+use crate::{game::entity::{armadillo::Armadillo, cannonball::Cannonball, chip::Chip, crate_entity::Crate, danger_cloud::DangerCloud, explosion::Explosion, fireball::Fireball, flame_jet::FlameJet, frog::Frog, goat::Goat, key::Key, launcher::Launcher, powerup::Powerup, props::EntityProps, Entity, EntityKind, Id}, level_pack_data::LevelPosition};
 
+#[derive(Clone)]
 struct EntityToSpawn {
     pos: Vec2,
     vel: Vec2,
     kind: EntityKind,
+    props: EntityProps,
     spawn_pos: Option<LevelPosition>,
 }
 
+#[derive(Clone)]
 pub struct EntitySpawner {
     id: u32,
     entities_to_spawn: Vec<EntityToSpawn>,
@@ -25,7 +28,11 @@ impl Default for EntitySpawner {
 
 impl EntitySpawner {
     pub fn add_entity(&mut self, pos: Vec2, vel: Vec2, kind: EntityKind, spawn_pos: Option<LevelPosition>) {
-        self.entities_to_spawn.push(EntityToSpawn { pos, vel, kind, spawn_pos });
+        self.entities_to_spawn.push(EntityToSpawn { pos, vel, kind, props: EntityProps::default(), spawn_pos });
+    }
+    // For entities placed in the level, which can have their own props
+    pub fn add_level_entity(&mut self, pos: Vec2, kind: EntityKind, props: EntityProps, spawn_pos: LevelPosition) {
+        self.entities_to_spawn.push(EntityToSpawn { pos, vel: props.vel(), kind, props, spawn_pos: Some(spawn_pos) });
     }
 
     pub fn spawn_entities(&mut self, entities: &mut Vec<Box<dyn Entity>>) {
@@ -45,14 +52,14 @@ impl EntitySpawner {
                 EntityKind::Chip(gravity) => Box::new(Chip::new(false, e.pos, if gravity { Some(e.vel) } else { None }, id)),
                 EntityKind::Life(gravity) => Box::new(Chip::new(true,  e.pos, if gravity { Some(e.vel) } else { None }, id)),
                 EntityKind::Frog(invuln) => Box::new(Frog::new(e.pos, e.vel, invuln, id)),
-                EntityKind::Goat => Box::new(Goat::new(e.pos, e.vel, id)),
-                EntityKind::Armadillo(invuln, spinning) => Box::new(Armadillo::new(e.pos, e.vel, spinning, invuln, id)),
+                EntityKind::Goat => Box::new(Goat::new(e.pos, e.vel, e.props.facing(), e.props.health(), id)),
+                EntityKind::Armadillo(invuln, spinning) => Box::new(Armadillo::new(e.pos, e.vel, spinning, invuln, e.props.facing(), id)),
                 EntityKind::DangerCloud => Box::new(DangerCloud::new(e.pos, e.vel, id)),
                 EntityKind::Explosion => Box::new(Explosion::new(e.pos, id)),
-                EntityKind::Launcher(kind) => Box::new(Launcher::new(kind, e.pos, id)),
+                EntityKind::Launcher(kind) => Box::new(Launcher::new(kind, e.pos, e.props.phase(), e.props.period(), id)),
                 EntityKind::Cannonball => Box::new(Cannonball::new(e.pos, e.vel, id)),
                 EntityKind::Fireball => Box::new(Fireball::new(e.pos, e.vel, id)),
-                EntityKind::FlameJet(dir) => Box::new(FlameJet::new(e.pos, dir, id)),
+                EntityKind::FlameJet(dir) => Box::new(FlameJet::new(e.pos, dir, e.props.phase(), e.props.period(), id)),
             };
             entities.push(entity);
         }
@@ -7,6 +7,7 @@ const FADE_TIME: f32 = 0.3;
 // The time the screen is fully black
 const BLACK_TIME: f32 = 0.1;
 
+#[derive(Clone)]
 pub struct Fader {
     fading: bool,
     moved: bool,
@@ -7,24 +7,32 @@ use camera::Camera;
 use entity_spawner::EntitySpawner;
 use fader::Fader;
 // use entity::{col_test::ColTest, frog::Frog, player::Player, Entity};
-use macroquad::{color::{Color, GREEN, RED, WHITE}, math::{vec2, Rect, Vec2}, rand::gen_range};
+use macroquad::{color::{Color, GREEN, RED, WHITE}, input::{is_key_down, is_key_pressed, KeyCode}, math::{vec2, Rect, Vec2}, rand::gen_range};
 use particles::{ParticleKind, Particles};
+use rewind::{Rewind, Snapshot, KEY_REWIND};
 use sign_display::SignDisplay;
 
-use crate::{editor::editor_level::EditorLevel, game::level::Level, level_pack_data::{level_pos_to_pos, LevelData}, resources::Resources, text_renderer::{render_text, Align, Font}, util::{draw_rect, rect}, VIEW_SIZE};
+use crate::{editor::{editor_level::EditorLevel, level_view::playtest_panel::PlaytestOptions}, game::level::Level, level_pack_data::{level_pos_to_pos, LevelData}, resources::Resources, text_renderer::{render_text, Align, Font}, util::{draw_rect, rect}, VIEW_SIZE};
 
-use super::{entity::{chip::Chip, Entity, EntityKind}, player::{FeetPowerup, HeadPowerup, Invuln, Player, PowerupKind}};
+use super::{death_map::Death, events::GameEvent, entity::{chip::Chip, Entity, EntityKind}, player::{FeetPowerup, HeadPowerup, Invuln, Player, PowerupKind, State}};
 
 pub mod camera;
 pub mod entity_spawner;
 pub mod particles;
 pub mod fader;
 pub mod sign_display;
+pub mod rewind;
 
 pub const PHYSICS_STEP: f32 = 1.0 / 120.0;
 pub const MAX_FALL_SPEED: f32 = 2.0;
 pub const GRAVITY: f32 = 0.045;
+pub const KEY_PRACTICE_CHECKPOINT: KeyCode = KeyCode::C;
+// Quicksaves are just clones of the whole scene, kept by whatever's running it (the editor's playtests, or the game when practicing)
+pub const KEY_QUICKSAVE: KeyCode = KeyCode::F5;
+pub const KEY_QUICKLOAD: KeyCode = KeyCode::F9;
 
+// Cloning a scene copies everything in it (the player, entities, level, etc), which is how quicksaves work
+#[derive(Clone)]
 pub struct Scene {
     level: Level,
 
@@ -37,6 +45,13 @@ pub struct Scene {
     fader: Fader,
     sign_display: SignDisplay,
     physics_update_timer: f32,
+    // The game speed assist, how much slower the physics are updated
+    game_speed: f32,
+    // Only kept while practicing
+    rewind: Option<Rewind>,
+    rewinding: bool,
+    // A checkpoint the player put down themselves while practicing
+    practice_checkpoint: Option<Vec2>,
 
     completed: bool,
 }
@@ -55,6 +70,9 @@ impl Scene {
             None => level.spawn(),
         };
 
+        // Starting from a checkpoint shouldn't count as reaching it
+        level.take_events();
+
         let mut scene = Self {
             level,
 
@@ -67,6 +85,10 @@ impl Scene {
             fader:        Fader::default(),
             sign_display: SignDisplay::default(),
             physics_update_timer: PHYSICS_STEP,
+            game_speed: 1.0,
+            rewind: None,
+            rewinding: false,
+            practice_checkpoint: None,
 
             completed: false,
         };
@@ -74,18 +96,29 @@ impl Scene {
         scene
     }
 
-    pub fn from_editor_level(editor_level: &EditorLevel, player_spawn: Option<Vec2>) -> Self {
+    pub fn from_editor_level(editor_level: &EditorLevel, player_spawn: Option<Vec2>, options: &PlaytestOptions) -> Self {
         // TODO: ... We can set world as 0 since we don't really care at all, as we're in the editor... for now?! 
-        let level = LevelData::from_editor_level(editor_level, 0)
+        let mut level = LevelData::from_editor_level(editor_level, 0)
             .to_level();
 
+        // Set the level up how the playtest options say
+        // The particles are thrown away, we don't want the locks exploding as soon as the level starts
+        for color in options.locks_destroyed() {
+            level.remove_lock_blocks(color, &mut Particles::default());
+        }
+        if let Some(enabled) = options.switch_state {
+            level.set_switch_state(enabled);
+        }
+        // Same with the locks, they weren't opened by the player
+        level.take_events();
+
         let player_spawn = player_spawn.unwrap_or(editor_level.spawn());
 
         let mut scene = Self {
             level,
 
             camera: Camera::new(player_spawn),
-            player: Player::new(player_spawn, None, None),
+            player: Player::new(player_spawn, options.head_powerup, options.feet_powerup),
             entities:     Vec::with_capacity(64),
             entity_spawner: EntitySpawner::default(),
             particles: Particles::default(),
@@ -93,6 +126,10 @@ impl Scene {
             fader: Fader::default(),
             sign_display: SignDisplay::default(),
             physics_update_timer: PHYSICS_STEP,
+            game_speed: 1.0,
+            rewind: None,
+            rewinding: false,
+            practice_checkpoint: None,
 
             completed: false,
         };
@@ -115,8 +152,8 @@ impl Scene {
         // The player uses the ids to stop them from grabbing the same thing too quickly!!
         // it's not useless! and just as i was about to remove them
 
-        for (&spawn_pos, &k) in self.level.entity_spawns().iter() {
-            self.entity_spawner.add_entity(level_pos_to_pos(spawn_pos) + k.tile_offset(), Vec2::ZERO, k, Some(spawn_pos));
+        for (&spawn_pos, &(k, props)) in self.level.entity_spawns().iter() {
+            self.entity_spawner.add_level_entity(level_pos_to_pos(spawn_pos) + k.tile_offset(), k, props, spawn_pos);
         }
         // Spawn all of them
         self.entity_spawner.spawn_entities(&mut self.entities);
@@ -128,9 +165,102 @@ impl Scene {
     pub fn completed(&self) -> bool {
         self.completed
     }
+    // Where and how the player died, only given once so it's only remembered once
+    pub fn take_death(&mut self) -> Option<Death> {
+        self.player.take_death()
+    }
+    // Everything that's happened since this was last called
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        self.level.take_events()
+    }
+    // The indices of the signs that have been read
+    pub fn read_signs(&self) -> impl Iterator<Item = usize> + '_ {
+        self.level.signs().iter().enumerate().filter(|(_, s)| s.read()).map(|(i, _)| i)
+    }
+    // How many of the chips placed in the level haven't been collected
+    pub fn chips_left(&self) -> usize {
+        self.entities.iter().filter(|e| e.kind() == EntityKind::Chip(false)).count()
+    }
     pub fn dead(&self) -> bool {
         self.player.dead_stop()
     }
+    pub fn set_assists(&mut self, invincible: bool, game_speed: f32) {
+        self.player.set_invincible(invincible);
+        self.game_speed = game_speed;
+    }
+    pub fn set_hurt_kills(&mut self, hurt_kills: bool) {
+        self.player.set_hurt_kills(hurt_kills);
+    }
+    pub fn practicing(&self) -> bool {
+        self.rewind.is_some()
+    }
+    pub fn set_practicing(&mut self, practicing: bool) {
+        match (practicing, self.practicing()) {
+            (true, false) => self.rewind = Some(Rewind::default()),
+            (false, true) => {
+                self.rewind = None;
+                self.practice_checkpoint = None;
+            }
+            _ => {}
+        }
+    }
+    pub fn practice_checkpoint(&self) -> Option<Vec2> {
+        self.practice_checkpoint
+    }
+    // Moves the player to a practice checkpoint, should only be called when the scene's just been made
+    pub fn set_practice_checkpoint(&mut self, pos: Vec2) {
+        self.practice_checkpoint = Some(pos);
+        self.player.set_pos(pos);
+        self.camera = Camera::new(pos);
+    }
+
+    // Practice checkpoints can only be put down somewhere the player's stood safely
+    fn update_practice_checkpoint(&mut self) {
+        let safe = matches!(self.player.state(), State::Standing | State::Moving)
+            && !self.player.dead()
+            && !matches!(self.player.invuln(), Invuln::Damage(_));
+        if self.practicing() && safe && is_key_pressed(KEY_PRACTICE_CHECKPOINT) {
+            self.practice_checkpoint = Some(self.player.pos());
+            self.level.add_event(GameEvent::PracticeCheckpoint);
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let mut player = self.player.clone();
+        // The death has already been recorded, we don't want it recorded again after rewinding
+        player.take_death();
+        Snapshot {
+            player,
+            entities: self.entities.clone(),
+            entity_spawner: self.entity_spawner.clone(),
+            level: self.level.snapshot(),
+            camera: self.camera.clone(),
+        }
+    }
+
+    // Goes back a physics step for every step of time the rewind key is held
+    // Returns false if we aren't rewinding, so the scene should update as normal
+    fn update_rewind(&mut self, deltatime: f32, resources: &Resources) -> bool {
+        self.rewinding = self.rewind.is_some() && is_key_down(KEY_REWIND) && !self.completed;
+        let Some(rewind) = self.rewind.as_mut().filter(|_| self.rewinding) else {
+            return false;
+        };
+
+        self.physics_update_timer += deltatime * self.game_speed;
+        while self.physics_update_timer >= PHYSICS_STEP {
+            self.physics_update_timer -= PHYSICS_STEP;
+            if let Some(snapshot) = rewind.pop() {
+                self.player = snapshot.player;
+                self.entities = snapshot.entities;
+                self.entity_spawner = snapshot.entity_spawner;
+                self.camera = snapshot.camera;
+                self.level.restore(&snapshot.level);
+            }
+        }
+        self.particles.update(&self.camera);
+        self.level.update_if_should(resources);
+        true
+    }
     pub fn player_screen_space_center(&self) -> Vec2 {
         self.player.pos() + 8.0 - self.camera.pos()
     }
@@ -141,7 +271,7 @@ impl Scene {
         self.player.feet_powerup()
     }
 
-    pub fn update(&mut self, chips: &mut usize, lives: &mut usize, deltatime: f32, resources: &mut Resources) {
+    pub fn update(&mut self, deltatime: f32, resources: &mut Resources) {
         self.fader.update(deltatime);
         self.sign_display.update();
 
@@ -158,6 +288,10 @@ impl Scene {
 
         self.player.update_move_dir();
         if freeze { return; }
+        if self.update_rewind(deltatime, resources) {
+            return;
+        }
+        self.update_practice_checkpoint();
         self.player.update(&mut self.entities, &mut self.camera, &mut self.fader, &mut self.sign_display, &mut self.level, resources);
 
         for e in &mut self.entities {
@@ -165,8 +299,17 @@ impl Scene {
         }
 
         // Update all of the physics in a fixed time-step
-        self.physics_update_timer += deltatime;
+        // (chips and lives don't count while rewinding's possible, otherwise they could be rewound and collected again forever!)
+        let was_dead = self.player.dead();
+        let prev_checkpoint = self.level.checkpoint();
+        self.physics_update_timer += deltatime * self.game_speed;
         while self.physics_update_timer >= PHYSICS_STEP &&!self.completed {
+            if self.rewind.is_some() {
+                let snapshot = self.snapshot();
+                if let Some(rewind) = &mut self.rewind {
+                    rewind.push(snapshot);
+                }
+            }
 
             self.player.physics_update(&mut self.entities, &mut self.entity_spawner, &mut self.particles, &mut self.level, resources);
 
@@ -219,7 +362,9 @@ impl Scene {
                 if matches!(self.entities[i].kind(), EntityKind::Chip(_)) {
                     if self.entities[i].hitbox().overlaps(&self.player.chip_hitbox()) {
                         particle_col = Some((self.entities[i].hitbox().center(), Chip::particle_color(false)));
-                        *chips += 1;
+                        if self.rewind.is_none() {
+                            self.level.add_event(GameEvent::ChipCollected);
+                        }
                         self.entities.remove(i);
                     }
                 }
@@ -229,7 +374,9 @@ impl Scene {
                         particle_col = Some((self.entities[i].hitbox().center(), Chip::particle_color(true)));
                         self.particles.add_particle(self.entities[i].hitbox().center(), vec2(0.0, -0.5), ParticleKind::OneUp);
                         self.entities.remove(i);
-                        *lives += 1;
+                        if self.rewind.is_none() {
+                            self.level.add_event(GameEvent::LifeCollected);
+                        }
                     }
                 }
                 // Collecting powerups
@@ -249,6 +396,7 @@ impl Scene {
                         particle_col = Some((center, kind.particle_color()));
                         collected_powerup = true;
                         self.player.collect_powerup(kind, center, &mut self.particles, &mut self.entity_spawner);
+                        self.level.add_event(GameEvent::PowerupCollected(kind));
                         self.entities.remove(i);
                     }
                 }
@@ -277,6 +425,14 @@ impl Scene {
             self.particles.update(&self.camera);
         }
 
+        if let Some(death) = self.player.death().filter(|_| !was_dead) {
+            self.level.add_event(GameEvent::PlayerDied(death.cause));
+        }
+        // Reaching a real checkpoint replaces the practice one
+        if self.level.checkpoint() != prev_checkpoint {
+            self.practice_checkpoint = None;
+        }
+
         self.level.update_bumped_tiles(deltatime);
         self.level.update_if_should(resources);
     }
@@ -287,6 +443,10 @@ impl Scene {
         draw_rect(view_rect, self.level.bg_col());
         self.level.render_bg(camera_pos, resources);
         self.level.render_below(camera_pos, resources);
+        if let Some(pos) = self.practice_checkpoint {
+            let bob_amount = (resources.tile_animation_timer().rem_euclid(2.0) >= 1.0) as usize as f32 - 1.0;
+            resources.draw_rect(pos + vec2(0.0, bob_amount) - camera_pos, Rect::new(176.0, 0.0, 16.0, 16.0), false, false, Color::new(1.0, 1.0, 1.0, 0.6), resources.entity_atlas());
+        }
 
         for e in &self.entities {
             e.draw(&self.player, camera_pos, resources);
@@ -306,7 +466,11 @@ impl Scene {
         // Lives
         render_text("- fox -",Color::from_hex(0xf77622), vec2( 40.0,  8.0), vec2(1.0, 1.0), Align::Mid, Font::Large, resources);
         render_text("*", WHITE,  vec2( 40.0, 24.0), vec2(1.0, 1.0), Align::Mid, Font::Large, resources);
-        render_text(&format!("{lives}"), WHITE,  vec2( 60.0, 24.0), vec2(1.0, 1.0), Align::Mid, Font::Large, resources);
+        let lives = match self.practicing() {
+            true  => String::from("-"),
+            false => format!("{lives}"),
+        };
+        render_text(&lives, WHITE,  vec2( 60.0, 24.0), vec2(1.0, 1.0), Align::Mid, Font::Large, resources);
         resources.draw_rect(vec2(13.0, 16.0), Rect::new(192.0, 16.0, 16.0, 15.0), false, false, WHITE, resources.entity_atlas());
         
         // Powerups
@@ -338,6 +502,14 @@ impl Scene {
             render_text(&format!("state: {:?}", self.player.state()), RED, vec2(10.0, 70.0), Vec2::ONE, Align::End, Font::Small, resources);    
         }
 
+        if let Some(rewind) = &self.rewind {
+            let text = match self.rewinding {
+                true  => format!("<< Rewinding {:.1}s", rewind.seconds_left()),
+                false => String::from("R rewind, C checkpoint, F5/F9 save"),
+            };
+            render_text(&text, WHITE, vec2(VIEW_SIZE.x / 2.0, VIEW_SIZE.y - 10.0), Vec2::ONE, Align::Mid, Font::Small, resources);
+        }
+
         self.sign_display.draw(resources);
         self.fader.draw();
         self.camera.draw(debug);
@@ -61,6 +61,7 @@ impl PartialOrd for ParticleKind {
 }
 
 
+#[derive(Clone)]
 struct Particle {
     pos: Vec2,
     vel: Vec2,
@@ -188,6 +189,7 @@ impl Particle {
     }
 }
 
+#[derive(Clone)]
 pub struct Particles {
     particles: Vec<Particle>,
 }
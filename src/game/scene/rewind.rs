@@ -0,0 +1,56 @@
+// Rewinding time, for practicing the hard bits of levels
+// Every physics step a snapshot of the player, entities and level is kept, and holding the rewind key goes back through them one step at a time.
+// Only the last few seconds are kept, so it doesn't keep eating memory the longer a level is played
+
+use std::collections::VecDeque;
+
+use macroquad::input::KeyCode;
+
+use crate::game::{entity::Entity, level::LevelSnapshot, player::Player};
+
+use super::{camera::Camera, entity_spawner::EntitySpawner};
+
+pub const KEY_REWIND: KeyCode = KeyCode::R;
+const REWIND_SECONDS: usize = 5;
+// PHYSICS_STEP is 1/120, so this is one snapshot for each step
+const MAX_SNAPSHOTS: usize = REWIND_SECONDS * 120;
+
+#[derive(Clone)]
+pub struct Snapshot {
+    pub player: Player,
+    pub entities: Vec<Box<dyn Entity>>,
+    pub entity_spawner: EntitySpawner,
+    pub level: LevelSnapshot,
+    pub camera: Camera,
+}
+
+#[derive(Default)]
+pub struct Rewind {
+    snapshots: VecDeque<Snapshot>,
+}
+
+// Cloning the scene (for quicksaves) doesn't need a copy of the whole history
+impl Clone for Rewind {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl Rewind {
+    pub fn push(&mut self, snapshot: Snapshot) {
+        if self.snapshots.len() >= MAX_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    // The most recent snapshot, or None if we've gone back as far as we can
+    pub fn pop(&mut self) -> Option<Snapshot> {
+        self.snapshots.pop_back()
+    }
+
+    // How many seconds can be rewound
+    pub fn seconds_left(&self) -> f32 {
+        self.snapshots.len() as f32 / 120.0
+    }
+}
@@ -6,7 +6,7 @@ use crate::{resources::Resources, text_renderer::{render_text, Align, Font}, VIE
 
 const BG_COL: Color = color_u8!(0, 0, 0, 100);
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct SignDisplay {
     lines: Option<[String; 4]>,
     closed_this_frame: bool,
@@ -2,7 +2,7 @@ use macroquad::{color::{Color, BLACK, RED, WHITE}, math::{vec2, Rect, Vec2}, sha
 
 use crate::{level_pack_data::LevelPackData, resources::Resources, text_renderer::{render_text, Align, Font}, util::{draw_rect, rect}, VIEW_SIZE};
 
-use super::player::{FeetPowerup, HeadPowerup, Player};
+use super::{difficulty::Difficulty, player::{FeetPowerup, HeadPowerup, Player}};
 
 #[derive(Default, Debug)]
 pub enum TransitionKind {
@@ -10,10 +10,10 @@ pub enum TransitionKind {
     None,
     // Pack
     PackStart(String, String), // name, author
-    PackFinish(String, String, Option<HeadPowerup>, Option<FeetPowerup>, usize, usize, usize, String), // name, author, powerups, chips, deaths, gameovers, levels
+    PackFinish(String, String, Option<HeadPowerup>, Option<FeetPowerup>, usize, usize, usize, String, bool), // name, author, powerups, chips, deaths, gameovers, levels, assists used
     
     // Level transitions
-    Intro(String, String, String, String, u8, Option<HeadPowerup>, Option<FeetPowerup>, usize), // pack name, author, name, world, powerups, lives
+    Intro(String, String, String, String, u8, Option<HeadPowerup>, Option<FeetPowerup>, usize, Difficulty), // pack name, author, name, world, powerups, lives, difficulty
 
     Finish(Vec2), // Center
     Death(Vec2),  // Center
@@ -69,12 +69,12 @@ impl Transition {
         self.kind = TransitionKind::None;
         self.timer = 0.0;
     }
-    pub fn begin_pack_finish(&mut self, name: String, author: String, head: Option<HeadPowerup>, feet: Option<FeetPowerup>, chips: usize, deaths: usize, gameovers: usize, timer: String) {
-        self.kind = TransitionKind::PackFinish(name, author, head, feet, chips, deaths, gameovers, timer);
+    pub fn begin_pack_finish(&mut self, name: String, author: String, head: Option<HeadPowerup>, feet: Option<FeetPowerup>, chips: usize, deaths: usize, gameovers: usize, timer: String, assisted: bool) {
+        self.kind = TransitionKind::PackFinish(name, author, head, feet, chips, deaths, gameovers, timer, assisted);
         self.timer = 0.0;
     }
-    pub fn begin_intro(&mut self, pack_name: String, author: String, name: String, world: String, world_num: u8, head: Option<HeadPowerup>, feet: Option<FeetPowerup>, lives: usize) {
-        self.kind = TransitionKind::Intro(pack_name, author, name, world, world_num, head, feet, lives);
+    pub fn begin_intro(&mut self, pack_name: String, author: String, name: String, world: String, world_num: u8, head: Option<HeadPowerup>, feet: Option<FeetPowerup>, lives: usize, difficulty: Difficulty) {
+        self.kind = TransitionKind::Intro(pack_name, author, name, world, world_num, head, feet, lives, difficulty);
         self.timer = 0.0;
     }
     pub fn begin_finish(&mut self, center: Vec2) {
@@ -138,7 +138,7 @@ impl Transition {
             render_text(author, fg_col, vec2(VIEW_SIZE.x / 2.0, 130.0), vec2(2.0, 2.0), Align::Mid, Font::Small, resources);
             render_text("by", Color::new(0.5, 0.5, 0.5, fg_col.a), vec2(VIEW_SIZE.x / 2.0, 105.0), Vec2::ONE, Align::Mid, Font::Small, resources);
         }
-        else if let TransitionKind::PackFinish(name, author, head, feet, chips, deaths, gameovers, timer) = &self.kind {
+        else if let TransitionKind::PackFinish(name, author, head, feet, chips, deaths, gameovers, timer, assisted) = &self.kind {
             draw_rect(screen_rect, BLACK);
             // Pack info
             render_text("YOU WIN!", WHITE, vec2(VIEW_SIZE.x / 2.0, 16.0), vec2(2.0, 2.0), Align::Mid, Font::Small, resources);
@@ -159,6 +159,9 @@ impl Transition {
             }
             // Timer
             render_text(timer, WHITE, vec2(VIEW_SIZE.x / 8.0 * 5.0, 64.0), vec2(1.0, 1.0), Align::Mid, Font::Small, resources);
+            if *assisted {
+                render_text("(assisted)", Color::from_hex(0x888888), vec2(VIEW_SIZE.x / 8.0 * 5.0, 76.0), vec2(1.0, 1.0), Align::Mid, Font::Small, resources);
+            }
 
             let player_size = 3.0;
             let player_pos = vec2((VIEW_SIZE.x - player_size * 16.0) / 2.0, 110.0);
@@ -173,7 +176,7 @@ impl Transition {
             // Cover rect
             draw_rect(screen_rect, Color::new(0.0, 0.0, 0.0, 1.0 - self.timer.clamp(0.0, 1.0)));
         }
-        else if let TransitionKind::Intro(pack_name, author, level, world, world_num, head, feet, lives) = &self.kind {
+        else if let TransitionKind::Intro(pack_name, author, level, world, world_num, head, feet, lives, difficulty) = &self.kind {
             let bg_col = Color::new(0.0, 0.0, 0.0, (5.0 - self.timer).clamp(0.0, 1.0));
 
             draw_rect(screen_rect, bg_col);
@@ -189,6 +192,7 @@ impl Transition {
                     render_text(&world, WHITE, vec2(VIEW_SIZE.x / 2.0, 40.0), vec2(1.0, 1.0), Align::Mid, Font::Small, resources);
                 }
                 render_text(&level, WHITE, vec2(VIEW_SIZE.x / 2.0, 60.0), vec2(2.0, 2.0), Align::Mid, Font::Small, resources);
+                render_text(difficulty.name(), difficulty.color(), vec2(VIEW_SIZE.x / 2.0, 84.0), vec2(1.0, 1.0), Align::Mid, Font::Small, resources);
                 // Lives
                 resources.draw_rect(vec2(VIEW_SIZE.x / 2.0 - 27.0, 172.0), Rect::new(192.0, 16.0, 16.0, 15.0), false, false, WHITE, resources.entity_atlas());
                 render_text("*",                 WHITE,  vec2(VIEW_SIZE.x / 2.0, 180.0), vec2(1.0, 1.0), Align::Mid, Font::Large, resources);
@@ -0,0 +1,168 @@
+// Rendering whole levels to images on the cpu, so overviews of levels can be made without the game (or a gpu) running
+// Running the game with '--render <pack>' writes every level of '<pack>.fox' to '<pack>_01.png', '<pack>_02.png', etc...
+//
+// Tiles are drawn the same as in game (connected textures and all), but entities, signs, doors, etc... are just coloured markers
+// The tileset for Tiled maps is made here too, from the same atlas
+
+use macroquad::{color::Color, color_u8, math::{vec2, Vec2}, prelude::ImageFormat, texture::Image};
+
+use crate::{editor::editor_level::EditorLevel, game::level::{tile::{Tile, TileDataManager, TileTextureRenderType}, Level, TileDrawKind, TileRenderData}, level_pack_data::LevelPackData, resources::TILES_TEXTURE};
+
+const SPAWN_COL:      Color = color_u8!( 99, 207,  71, 255);
+const FINISH_COL:     Color = color_u8!(207,  72,  71, 255);
+const CHECKPOINT_COL: Color = color_u8!( 71, 109, 207, 255);
+const ENTITY_COL:     Color = color_u8!(207,  71, 195, 255);
+const SIGN_COL:       Color = color_u8!(207, 179,  71, 255);
+const DOOR_COL:       Color = color_u8!( 71, 207, 200, 255);
+// Background tiles are drawn darker, the same as in game
+const BACKGROUND_TINT: f32 = 150.0 / 255.0;
+
+// Draws a pixel over the top of what's already there
+// (what's underneath can be see-through too, for the tileset)
+fn blend_pixel(image: &mut Image, x: u32, y: u32, color: Color) {
+    if x >= image.width() as u32 || y >= image.height() as u32 || color.a <= 0.0 {
+        return;
+    }
+    let under = image.get_pixel(x, y);
+    let alpha = color.a + under.a * (1.0 - color.a);
+    let mix = |top: f32, bottom: f32| (top * color.a + bottom * under.a * (1.0 - color.a)) / alpha;
+    image.set_pixel(x, y, Color::new(mix(color.r, under.r), mix(color.g, under.g), mix(color.b, under.b), alpha));
+}
+
+// Copies part of the tile atlas into the image
+fn draw_atlas_part(image: &mut Image, atlas: &Image, source: (u32, u32), size: u32, dest: (u32, u32), tint: f32) {
+    for y in 0..size {
+        for x in 0..size {
+            if source.0 + x >= atlas.width() as u32 || source.1 + y >= atlas.height() as u32 {
+                continue;
+            }
+            let c = atlas.get_pixel(source.0 + x, source.1 + y);
+            blend_pixel(image, dest.0 + x, dest.1 + y, Color::new(c.r * tint, c.g * tint, c.b * tint, c.a));
+        }
+    }
+}
+
+fn draw_tile(image: &mut Image, atlas: &Image, render_data: &TileRenderData, tile_data_manager: &TileDataManager, tint: f32) {
+    let TileRenderData { tile, draw_kind, pos } = *render_data;
+    let texture = match tile_data_manager.data(tile).texture() {
+        Some(t) => t,
+        None => return,
+    };
+    // Animated tiles are just drawn on their first frame
+    let start_texture = match texture.render {
+        TileTextureRenderType::Fixed(texture) => texture,
+        TileTextureRenderType::Animated { frames, .. } => frames[0],
+    };
+    let tile_point = |texture: usize| (((texture % 16) * 16) as u32, ((texture / 16) * 16) as u32);
+    let (x, y) = (pos.x as u32, pos.y as u32);
+
+    match draw_kind {
+        TileDrawKind::Single(offset) => draw_atlas_part(image, atlas, tile_point(start_texture + offset), 16, (x, y), tint),
+        TileDrawKind::Quarters(tl, tr, bl, br) => {
+            for (offset, qx, qy) in [(tl, 0, 0), (tr, 8, 0), (bl, 0, 8), (br, 8, 8)] {
+                let (sx, sy) = tile_point(start_texture + offset);
+                draw_atlas_part(image, atlas, (sx + qx, sy + qy), 8, (x + qx, y + qy), tint);
+            }
+        }
+    }
+}
+
+// An outlined square over a tile, with a see-through middle
+fn draw_marker(image: &mut Image, pos: Vec2, size: u32, color: Color) {
+    let (x, y) = (pos.x.max(0.0) as u32, pos.y.max(0.0) as u32);
+    let fill = Color { a: 0.35, ..color };
+    for py in 0..size {
+        for px in 0..size {
+            let edge = px == 0 || py == 0 || px == size - 1 || py == size - 1;
+            blend_pixel(image, x + px, y + py, if edge { color } else { fill });
+        }
+    }
+}
+
+pub fn render_level(editor_level: &EditorLevel, tile_data_manager: &TileDataManager, atlas: &Image) -> Image {
+    let (width, height) = (editor_level.width(), editor_level.height());
+    let mut image = Image::gen_image_color(width as u16 * 16, height as u16 * 16, editor_level.bg_col_as_color());
+
+    let (mut tiles_below, mut tiles_above, mut tiles_background) = (vec![], vec![], vec![]);
+    Level::update_tile_render_data(&mut tiles_below, &mut tiles_above, &mut tiles_background, None, editor_level.tiles(), editor_level.tiles_bg(), width, height, tile_data_manager);
+
+    for render_data in &tiles_background {
+        draw_tile(&mut image, atlas, render_data, tile_data_manager, BACKGROUND_TINT);
+    }
+    for render_data in tiles_below.iter().chain(&tiles_above) {
+        draw_tile(&mut image, atlas, render_data, tile_data_manager, 1.0);
+    }
+
+    for (pos, _, _) in editor_level.entities() {
+        draw_marker(&mut image, *pos + 2.0, 12, ENTITY_COL);
+    }
+    for (pos, _) in editor_level.signs() {
+        draw_marker(&mut image, *pos + 2.0, 12, SIGN_COL);
+    }
+    // Doors get a smaller marker where they lead to
+    for (_, pos, dest) in editor_level.doors() {
+        draw_marker(&mut image, *pos, 16, DOOR_COL);
+        draw_marker(&mut image, *dest + 4.0, 8, DOOR_COL);
+    }
+    for pos in editor_level.checkpoints() {
+        draw_marker(&mut image, *pos, 16, CHECKPOINT_COL);
+    }
+    draw_marker(&mut image, editor_level.spawn(),  16, SPAWN_COL);
+    draw_marker(&mut image, editor_level.finish(), 16, FINISH_COL);
+
+    image
+}
+
+// macroquad's export_png flips the rows (it expects images read back from the gpu, which are upside down),
+// but the level is drawn top-down, so it has to be flipped first to come out the right way up
+fn export_png_top_down(image: &Image, path: &str) {
+    let w = image.width();
+    let mut flipped = image.clone();
+    for (dest, src) in flipped.get_image_data_mut().chunks_mut(w).zip(image.get_image_data().chunks(w).rev()) {
+        dest.copy_from_slice(src);
+    }
+    flipped.export_png(path);
+}
+
+// Every tile on its own (not connected to anything) in a 16 wide grid, the tile with the id n being in the (n-1)th cell.
+// This is the tileset for Tiled maps, so the tiles look right in Tiled while the tile ids stay the same as the .fox ones
+pub fn export_tileset(path: &str) -> Result<(), String> {
+    let atlas = Image::from_file_with_format(TILES_TEXTURE, Some(ImageFormat::Png)).map_err(|_| String::from("Couldn't read the tile atlas!"))?;
+    let tile_data_manager = TileDataManager::default();
+    let mut image = Image::gen_image_color(16 * 16, 16 * 16, Color::new(0.0, 0.0, 0.0, 0.0));
+
+    for id in 1..=u8::MAX {
+        let Ok(tile) = Tile::try_from(id) else {
+            continue;
+        };
+        // Surrounded by nothing so it gets its 'lone' texture, as out of bounds counts as connected
+        let mut tiles = vec![Tile::Empty; 9];
+        tiles[4] = tile;
+        let (mut tiles_below, mut tiles_above, mut tiles_background) = (vec![], vec![], vec![]);
+        Level::update_tile_render_data(&mut tiles_below, &mut tiles_above, &mut tiles_background, None, &tiles, &vec![Tile::Empty; 9], 3, 3, &tile_data_manager);
+
+        let pos = vec2(((id - 1) % 16) as f32, ((id - 1) / 16) as f32) * 16.0;
+        for render_data in tiles_below.iter().chain(&tiles_above) {
+            draw_tile(&mut image, &atlas, &TileRenderData { pos, ..*render_data }, &tile_data_manager, 1.0);
+        }
+    }
+    export_png_top_down(&image, path);
+    Ok(())
+}
+
+// Returns how many levels were rendered
+pub fn render_pack(pack_file_name: &str) -> Result<usize, String> {
+    let bytes = std::fs::read(format!("{pack_file_name}.fox")).map_err(|e| format!("Couldn't read {pack_file_name}.fox: {e}"))?;
+    let pack = LevelPackData::from_bytes(pack_file_name.to_owned(), &bytes).ok_or_else(|| format!("{pack_file_name}.fox isn't a valid pack!"))?;
+    let atlas = Image::from_file_with_format(TILES_TEXTURE, Some(ImageFormat::Png)).map_err(|_| String::from("Couldn't read the tile atlas!"))?;
+    let tile_data_manager = TileDataManager::default();
+
+    let indices: Vec<usize> = (0..pack.levels().len()).collect();
+    for (i, editor_level) in pack.to_editor_levels(&indices).iter().enumerate() {
+        // Named the same way as the Tiled exports, e.g. 'my_pack_03.png'
+        let file_name = format!("{}_{:0>2}.png", pack_file_name, i + 1);
+        export_png_top_down(&render_level(editor_level, &tile_data_manager, &atlas), &file_name);
+        println!("Rendered '{}' to {file_name}", editor_level.name());
+    }
+    Ok(indices.len())
+}
@@ -2,7 +2,7 @@
 
 use macroquad::{color::Color, math::{vec2, Vec2}};
 
-use crate::{editor::{editor_level::{EditorLevel, MAX_SIGNS}, editor_level_pack::{EditorLevelPack, MAX_LEVELS}}, game::{entity::EntityKind, level::{things::{Door, DoorKind, Sign}, tile::Tile, Level}}, resources::Resources, text_renderer::Font};
+use crate::{editor::{editor_level::{EditorLevel, MAX_SIGNS}, editor_level_pack::{EditorLevelPack, MAX_LEVELS}}, game::{entity::{props::{props_len, EntityProps}, EntityKind}, level::{things::{Door, DoorKind, Sign}, tile::Tile, Level}}, text_renderer::typable_char};
 
 // Pack names and authors, level names
 pub const MAX_FIELD_LEN: usize = 24;
@@ -44,7 +44,7 @@ pub struct LevelData {
     checkpoints: Vec<LevelPosition>,
     signs: Vec<(LevelPosition, [String; 4])>,
     doors: Vec<(DoorKind, LevelPosition, LevelPosition)>,
-    entities: Vec<(LevelPosition, EntityKind)>
+    entities: Vec<(LevelPosition, EntityKind, EntityProps)>
 }
 
 pub fn pos_to_level_pos(pos: Vec2) -> LevelPosition {
@@ -65,6 +65,13 @@ impl LevelData {
     pub fn world(&self) -> u8 {
         self.world
     }
+    pub fn sign_count(&self) -> usize {
+        self.signs.len()
+    }
+    // Only the chips placed in the level, not ones that come out of crates
+    pub fn chip_count(&self) -> usize {
+        self.entities.iter().filter(|(_, k, _)| matches!(k, EntityKind::Chip(_))).count()
+    }
 
     // Turning an editor level into LevelData
     pub fn from_editor_level(editor_level: &EditorLevel, world: u8) -> Self {
@@ -92,7 +99,7 @@ impl LevelData {
                 .collect(),
             entities: editor_level.entities()
                 .iter()
-                .map(|(p, e)| (pos_to_level_pos(*p), *e))
+                .map(|(p, e, props)| (pos_to_level_pos(*p), *e, *props))
                 .collect(),
         }
     }
@@ -123,7 +130,7 @@ impl LevelData {
                 .collect(),
             self.entities
                 .iter()
-                .map(|(p, kind)| (level_pos_to_pos(*p), *kind))
+                .map(|(p, kind, props)| (level_pos_to_pos(*p), *kind, *props))
                 .collect(),
         )
     }
@@ -150,7 +157,10 @@ impl LevelData {
                 .iter()
                 .map(|(t, p, d)| Door::new(*t, level_pos_to_pos(*p), level_pos_to_pos(*d)))
                 .collect(),
-            self.entities.iter().cloned().collect(),
+            self.entities
+                .iter()
+                .map(|(p, kind, props)| (*p, (*kind, *props)))
+                .collect(),
         )
     }
 }
@@ -212,25 +222,42 @@ impl LevelPackData {
 
         EditorLevelPack::new(self.file_name.clone(), self.name.clone(), self.author.clone(), levels)
     }
+
+    // Turning only some of the levels into editor levels, for importing them into another pack.
+    // The first chosen level of each world keeps the world's name so the worlds survive the trip!
+    pub fn to_editor_levels(&self, indices: &[usize]) -> Vec<EditorLevel> {
+        let mut levels = Vec::with_capacity(indices.len());
+
+        let mut prev_world = 0;
+        for level in indices.iter().filter_map(|i| self.levels.get(*i)) {
+            let world_name = match level.world != prev_world && level.world != 0 {
+                false => String::new(),
+                true  => self.worlds.get(level.world as usize - 1).cloned().unwrap_or_default(),
+            };
+            prev_world = level.world;
+            levels.push(level.to_editor_level(world_name));
+        }
+        levels
+    }
 }
 
 // Decoding / encoding stuff below...
 
 // Guaranteed to be MAX_FIELD_LEN long, which is 24
-fn string_to_bytes(s: &String, resources: &Resources) -> [u8; MAX_FIELD_LEN] {
+fn string_to_bytes(s: &String) -> [u8; MAX_FIELD_LEN] {
     let mut bytes = [0; MAX_FIELD_LEN];
     for (i, c) in s.chars().enumerate() {
         if i >= MAX_FIELD_LEN {
             break;
         }
-        if resources.font_data_manager().font_data(Font::Small).typable_char(c) {
+        if typable_char(c) {
             bytes[i] = c as u8;
         }
     }
     bytes
 }
 
-fn bytes_to_string(begin: usize, bytes: &[u8], max_len: usize, resources: &Resources) -> Option<String> {
+fn bytes_to_string(begin: usize, bytes: &[u8], max_len: usize) -> Option<String> {
     let mut s = String::new();
 
     for i in 0..max_len {
@@ -242,7 +269,7 @@ fn bytes_to_string(begin: usize, bytes: &[u8], max_len: usize, resources: &Resou
         }
         let c = b as char;
         // If the char isn't valid, the string isn't valid!
-        if !resources.font_data_manager().font_data(Font::Small).typable_char(c) {
+        if !typable_char(c) {
             return None;
         }
         s.push(c);
@@ -253,11 +280,11 @@ fn bytes_to_string(begin: usize, bytes: &[u8], max_len: usize, resources: &Resou
 
 // Turning level data into bytes
 impl LevelData {
-    pub fn to_bytes(&self, resources: &Resources) -> Vec<u8> {
+    pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
         
         // Add the name
-        bytes.extend_from_slice(&string_to_bytes(&self.name, resources));
+        bytes.extend_from_slice(&string_to_bytes(&self.name));
         // Add the world number
         bytes.push(self.world);
         // Add the background color
@@ -293,10 +320,10 @@ impl LevelData {
         // Add the number of signs, their lines and positions
         bytes.push(self.signs.len() as u8);
         for (pos, lines) in &self.signs {
-            bytes.extend_from_slice(&string_to_bytes(&lines[0], resources)); // line 0
-            bytes.extend_from_slice(&string_to_bytes(&lines[1], resources)); // line 1
-            bytes.extend_from_slice(&string_to_bytes(&lines[2], resources)); // line 2
-            bytes.extend_from_slice(&string_to_bytes(&lines[3], resources)); // line 3
+            bytes.extend_from_slice(&string_to_bytes(&lines[0])); // line 0
+            bytes.extend_from_slice(&string_to_bytes(&lines[1])); // line 1
+            bytes.extend_from_slice(&string_to_bytes(&lines[2])); // line 2
+            bytes.extend_from_slice(&string_to_bytes(&lines[3])); // line 3
             bytes.push(pos.0); // x
             bytes.push(pos.1); // y
         }
@@ -309,17 +336,18 @@ impl LevelData {
             bytes.push(dest.0); // x
             bytes.push(dest.1); // y
         }
-        // Add the number of entities, their kinds and positions
+        // Add the number of entities, their kinds, positions, and props
         bytes.push(self.entities.len() as u8);
-        for (p, kind) in &self.entities {
+        for (p, kind, props) in &self.entities {
             bytes.push((*kind).into()); // kind (turned into u8)
             bytes.push(p.0); // x
             bytes.push(p.1); // y
+            bytes.extend_from_slice(&props.to_bytes());
         }
         bytes
     }
 
-    pub fn from_bytes(bytes: &[u8], cursor: &mut usize, resources: &Resources) -> Option<Self> {
+    pub fn from_bytes(bytes: &[u8], cursor: &mut usize, version: u8) -> Option<Self> {
         // Don't want to have to write *bytes.get(index)? each time... this closure makes it easier!
         // If only I could add the ? to the closure........ :c
         let get_byte = |index: usize| -> Option<u8> {
@@ -327,7 +355,7 @@ impl LevelData {
         };
 
         // Get the name and move the cursor
-        let name = bytes_to_string(*cursor, &bytes, 22, resources)?;
+        let name = bytes_to_string(*cursor, &bytes, 22)?;
         *cursor += MAX_FIELD_LEN;
 
         // Get the world number and move the cursor
@@ -390,7 +418,7 @@ impl LevelData {
         for _ in 0..signs_len {
             let mut lines = Vec::with_capacity(4);
             for _ in 0..4 {
-                lines.push(bytes_to_string(*cursor, &bytes, 24, resources)?);
+                lines.push(bytes_to_string(*cursor, &bytes, 24)?);
                 *cursor += MAX_FIELD_LEN;
             }
             let x = get_byte(*cursor)?;
@@ -414,7 +442,7 @@ impl LevelData {
         }
 
         // Get the entities
-        let mut entities: Vec<(LevelPosition, EntityKind)> = Vec::new();
+        let mut entities: Vec<(LevelPosition, EntityKind, EntityProps)> = Vec::new();
         let entities_len = get_byte(*cursor)?;
         *cursor += 1;
 
@@ -422,8 +450,17 @@ impl LevelData {
             let kind = get_byte(*cursor)?;
             let x = get_byte(*cursor+1)?;
             let y = get_byte(*cursor+2)?;
-            entities.push(((x, y), kind.try_into().ok()?));
             *cursor += 3;
+            // Packs from before version 1 don't have any props
+            let props = match version {
+                0 => EntityProps::default(),
+                _ => {
+                    let props = EntityProps::from_bytes(bytes.get(*cursor..)?, version)?;
+                    *cursor += props_len(version);
+                    props
+                }
+            };
+            entities.push(((x, y), kind.try_into().ok()?, props));
         }
 
         Some(Self { name, world, bg_col, width, height, tiles, tiles_bg, spawn, finish, checkpoints, signs, doors, entities })
@@ -431,47 +468,70 @@ impl LevelData {
 }
 
 const CHECKSUM: [u8; 17] = [0x6A, 0x75, 0x6D, 0x62, 0x6C, 0x65, 0x64, 0x46, 0x6F, 0x78, 0x20, 0x72, 0x75, 0x6C, 0x65, 0x73, 0x21];
+// Packs made before I added versions go straight into the name after the checksum, so to tell them apart
+// newer packs put this byte (which can never be a valid char) and then the version
+const VERSION_MARKER: u8 = 0xFE;
+// 1 - entities have props
+// 2 - entity props have a timing group
+pub const VERSION: u8 = 2;
 
 impl LevelPackData {
-    pub fn to_bytes(&self, resources: &Resources) -> Vec<u8> {
+    pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
 
         // Add the 'checksum' lololol
         bytes.extend_from_slice(&CHECKSUM);
+        // Add the version
+        bytes.push(VERSION_MARKER);
+        bytes.push(VERSION);
         // Add the name and author
-        bytes.extend_from_slice(&string_to_bytes(&self.name, resources));
-        bytes.extend_from_slice(&string_to_bytes(&self.author, resources));
+        bytes.extend_from_slice(&string_to_bytes(&self.name));
+        bytes.extend_from_slice(&string_to_bytes(&self.author));
         // Add the worlds
         bytes.push(self.worlds.len() as u8);
         for w in &self.worlds {
-            bytes.extend_from_slice(&string_to_bytes(w, resources));
+            bytes.extend_from_slice(&string_to_bytes(w));
         }
         // Add each level
         for l in &self.levels {
-            let level_bytes = l.to_bytes(resources);
+            let level_bytes = l.to_bytes();
             bytes.extend_from_slice(&level_bytes);
         }
 
         bytes
     }
 
-    pub fn from_bytes(file_name: String, bytes: &[u8], resources: &Resources) -> Option<Self> {
+    pub fn from_bytes(file_name: String, bytes: &[u8]) -> Option<Self> {
         // The cursor is where we are in 'bytes', makes it easier for me to decode i think :3
         // FUTURE ME HERE: the cursor makes this SO EASY!!!!! yippeeeeeeee
         let mut cursor = 0;
 
         // Get checksum
         for byte in CHECKSUM {
-            if bytes[cursor] != byte {
+            if bytes.get(cursor) != Some(&byte) {
                 return None;
             }
             cursor += 1;
         }
 
+        // Get the version, packs without one are version 0
+        let version = match bytes.get(cursor) {
+            Some(&VERSION_MARKER) => {
+                let version = *bytes.get(cursor + 1)?;
+                cursor += 2;
+                version
+            }
+            _ => 0,
+        };
+        // Don't even try to load packs from the future
+        if version > VERSION {
+            return None;
+        }
+
         // Get the name and the author
-        let name = bytes_to_string(cursor, bytes, 22, resources)?;
+        let name = bytes_to_string(cursor, bytes, 22)?;
         cursor += MAX_FIELD_LEN;
-        let author = bytes_to_string(cursor, bytes, 22, resources)?;
+        let author = bytes_to_string(cursor, bytes, 22)?;
         cursor += MAX_FIELD_LEN;
 
         // Get the worlds
@@ -480,7 +540,7 @@ impl LevelPackData {
         let mut worlds = Vec::with_capacity(world_count as usize);
         for _ in 0..world_count {
             // Get the name and add it
-            let world_name = bytes_to_string(cursor, bytes, 22, resources)?;
+            let world_name = bytes_to_string(cursor, bytes, 22)?;
             cursor += MAX_FIELD_LEN;
             worlds.push(world_name);
         }
@@ -489,7 +549,7 @@ impl LevelPackData {
         let mut levels: Vec<LevelData> = Vec::new();
         // Repeat until the cursor is out of the bounds of the file
         while cursor <= bytes.len() - 1 {
-            let level_data = LevelData::from_bytes(bytes, &mut cursor, resources);
+            let level_data = LevelData::from_bytes(bytes, &mut cursor, version);
             if let Some(p) = level_data {
                 levels.push(p);
             } else {
@@ -516,6 +576,8 @@ position (byte, byte) for LevelPosition
 "checksum" to immediately discard invalid files and make the packs look neat in a hex editor
 6A 75 6D 62 6C 65 64 46 6F 78 20 72 75 6C 65 73 21
 
+version marker (FE) and version (byte) - missing in version 0 packs
+
 name: string
 author: string
 
@@ -542,5 +604,6 @@ doors_len (byte)
 doors (series of (byte, position, position) for (teleporter, pos, dest))
 
 entities_len (byte)
-entities ((byte, position) for (entity kind, pos))
+entities ((byte, position, props) for (entity kind, pos, props))
+props (facing, phase, period, health, vel x, vel y, group) - all bytes, only in version 1 onwards (group is version 2 onwards)
 */
\ No newline at end of file
@@ -1,14 +1,17 @@
 
-use macroquad::{camera::{set_camera, set_default_camera, Camera2D}, color::{BLACK, WHITE}, input::is_key_pressed, math::{vec2, Rect, Vec2}, texture::{draw_texture_ex, render_target, set_default_filter_mode, DrawTextureParams, FilterMode}, time::get_frame_time, window::{clear_background, next_frame, Conf}};
+use macroquad::{camera::{set_camera, set_default_camera, Camera2D}, color::{BLACK, WHITE}, input::{is_key_pressed, KeyCode}, math::{vec2, Rect, Vec2}, texture::{draw_texture_ex, render_target, set_default_filter_mode, DrawTextureParams, FilterMode}, time::get_frame_time, window::{clear_background, next_frame, Conf}};
 use menu::Menu;
 use resources::Resources;
-use ui::Ui;
+use ui::{toast::{ToastKind, ToastManager}, Ui};
 
 pub mod util;
 pub mod resources;
 pub mod text_renderer;
 pub mod ui;
 pub mod level_pack_data;
+pub mod level_image;
+pub mod screenshot;
+pub mod settings;
 
 // The different game states
 pub mod menu;
@@ -31,12 +34,30 @@ fn window_conf()-> Conf {
 }
 
 pub trait GameState {
-    fn update(&mut self, deltatime: f32, ui: &mut Ui, resources: &mut Resources, next_state: &mut Option<Box<dyn GameState>>);
+    fn update(&mut self, deltatime: f32, ui: &mut Ui, resources: &mut Resources, toast_manager: &mut ToastManager, next_state: &mut Option<Box<dyn GameState>>);
     fn draw(&self, ui: &Ui, resources: &Resources, debug: bool);
 }
 
-#[macroquad::main(window_conf())]
-async fn main() {
+fn main() {
+    // '--render <pack>' renders the pack's levels to pngs and quits, without ever opening a window
+    let args: Vec<String> = std::env::args().collect();
+    if let [_, flag, pack] = args.as_slice() {
+        if flag == "--render" {
+            match level_image::render_pack(pack) {
+                Ok(count) => println!("Rendered {count} levels"),
+                Err(e) => {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+    }
+
+    macroquad::Window::from_config(window_conf(), run());
+}
+
+async fn run() {
     // Seed the randomness
     macroquad::rand::srand(macroquad::miniquad::date::now() as u64);
 
@@ -59,27 +80,28 @@ async fn main() {
     world_cam.render_target = Some(render_target.clone());
 
     let mut debug = false;
-    let mut game_state: Box<dyn GameState> = Box::new(Menu::new(None));
+    // Toasts are shared by every game state, so they stick around when switching between them
+    let mut toast_manager = ToastManager::default();
+    let mut game_state: Box<dyn GameState> = Box::new(Menu::new(None, &mut toast_manager));
     let mut next_state: Option<Box<dyn GameState>> = None;
 
     loop {
         ui.begin_frame();
 
         // Toggling debug mode
-        if is_key_pressed(macroquad::input::KeyCode::F1) {
+        if is_key_pressed(KeyCode::F1) {
             debug = !debug;
         }
-        // I was gonna add taking screenshots but that'd require making a toastmanager global and tbh... im kinda done with the coding.
-        // screenshots would be kinda simple too but like.. at what point does it straight up not matter?
 
         // Update the game state
         let deltatime = get_frame_time();
         resources.update_tile_animation_timer(deltatime);
-        game_state.update(deltatime, &mut ui, &mut resources, &mut next_state);
+        toast_manager.update(deltatime);
+        game_state.update(deltatime, &mut ui, &mut resources, &mut toast_manager, &mut next_state);
 
         if let Some(state) = next_state.take() {
             game_state = state;
-            game_state.update(deltatime, &mut ui, &mut resources, &mut next_state);
+            game_state.update(deltatime, &mut ui, &mut resources, &mut toast_manager, &mut next_state);
         }
 
         // Draw to the render target
@@ -87,6 +109,18 @@ async fn main() {
         game_state.draw(&ui, &resources, debug);
         ui.draw(&resources);
 
+        // Taking a screenshot before the toasts are drawn so they aren't in it
+        if is_key_pressed(KeyCode::F12) {
+            // Nothing's actually drawn to the render target until the camera's set again,
+            // so do that first or the screenshot would be of the last frame
+            set_camera(&world_cam);
+            match screenshot::save_screenshot(&render_target.texture) {
+                Ok(name) => toast_manager.add_toast(format!("Saved {name}"), ToastKind::Success),
+                Err(e)   => toast_manager.add_toast(format!("Couldn't save screenshot: {e}"), ToastKind::Error),
+            }
+        }
+        toast_manager.draw(&resources);
+
         // Draw the render target
         let r = Ui::render_target_rect();
         set_default_camera();
@@ -3,7 +3,7 @@ use std::{f32::consts::PI, fs};
 use macroquad::{color::{Color, BLUE, GREEN, LIGHTGRAY, ORANGE, PURPLE, RED, WHITE, YELLOW}, color_u8, input::{is_key_pressed, KeyCode}, math::{vec2, Rect, Vec2}, miniquad::window::order_quit, prelude::rand};
 use submenu::{Submenu, SubmenuState};
 
-use crate::{editor::{editor_level::BG_SKY, Editor}, game::Game, level_pack_data::LevelPackData, resources::Resources, text_renderer::{render_text, Align, Font}, ui::{button::Button, toast::{ToastKind, ToastManager}, Ui}, util::draw_rect, GameState, VIEW_SIZE};
+use crate::{editor::{editor_level::BG_SKY, Editor}, game::{difficulty::Difficulty, Game}, level_pack_data::LevelPackData, resources::Resources, text_renderer::{render_text, Align, Font}, ui::{button::Button, toast::{ToastKind, ToastManager}, Ui}, util::draw_rect, GameState, VIEW_SIZE};
 
 const BG_COL: Color = color_u8!(BG_SKY.0, BG_SKY.1, BG_SKY.2, 255);
 
@@ -16,11 +16,12 @@ pub mod submenu;
 
 pub struct Menu {
     logo_timer: f32,
-    toast_manager: ToastManager,
     submenu: Submenu,
 
     pack: usize,
     pack_list: Vec<String>,
+    // After pressing play, the pack waits here while a difficulty is picked
+    difficulty_pack: Option<LevelPackData>,
 
     // Buttons
     button_pack_refresh: Button,
@@ -29,24 +30,27 @@ pub struct Menu {
     button_play: Button,
     button_edit_cur: Button,
     button_help: Button,
+    button_achievements: Button,
     button_editor: Button,
     button_credits: Button,
+    button_settings: Button,
     button_exit: Button,
+    button_difficulties: Vec<(Difficulty, Button)>,
+    button_difficulty_back: Button,
 }
 
 impl Menu {
-    pub fn new(current_pack: Option<String>) -> Self {
-        let mut toast_manager = ToastManager::default();
-        let pack_list = Self::get_pack_list(&mut toast_manager);
+    pub fn new(current_pack: Option<String>, toast_manager: &mut ToastManager) -> Self {
+        let pack_list = Self::get_pack_list(toast_manager);
         let pack = Self::index_in_pack(current_pack, &pack_list);
 
         Self {
             logo_timer: 0.0,
-            toast_manager,
             submenu: Submenu::default(),
             
             pack, 
             pack_list,
+            difficulty_pack: None,
 
             button_pack_refresh: Button::new(Rect::new(PACK_SELECTOR_BEGIN.x + 112.0, PACK_SELECTOR_BEGIN.y, 12.0, 12.0), Some(String::from("🔄")), Some(String::from("Refresh list"))),
             button_pack_prev: Button::new(Rect::new(PACK_SELECTOR_BEGIN.x + 84.0, PACK_SELECTOR_BEGIN.y, 12.0, 12.0), Some(String::from("🮤")), None),
@@ -54,9 +58,16 @@ impl Menu {
             button_play: Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y, BUTTONS_WIDTH - 18.0, 16.0), Some(String::from("Play!")), None),
             button_edit_cur: Button::new(Rect::new(BUTTONS_BEGIN.x + BUTTONS_WIDTH - 16.0, BUTTONS_BEGIN.y, 16.0, 16.0), None, Some(String::from("Edit pack"))),
             button_help:    Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 1.0, BUTTONS_WIDTH, 16.0), Some(String::from("How to play")), None),
-            button_editor:  Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 2.0, BUTTONS_WIDTH, 16.0), Some(String::from("Editor")), None),
-            button_credits: Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 3.0, BUTTONS_WIDTH, 16.0), Some(String::from("Credits")), None),
-            button_exit:    Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 4.0, BUTTONS_WIDTH, 16.0), Some(String::from("Exit")), None),
+            button_achievements: Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 2.0, BUTTONS_WIDTH, 16.0), Some(String::from("Achievements")), None),
+            button_editor:  Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 3.0, BUTTONS_WIDTH, 16.0), Some(String::from("Editor")), None),
+            button_credits: Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 4.0, BUTTONS_WIDTH, 16.0), Some(String::from("Credits")), None),
+            button_settings: Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 5.0, BUTTONS_WIDTH, 16.0), Some(String::from("Settings")), None),
+            button_exit:    Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 6.0, BUTTONS_WIDTH, 16.0), Some(String::from("Exit")), None),
+            button_difficulties: Difficulty::ALL.iter().enumerate().map(|(i, &d)| {
+                let rect = Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * i as f32, BUTTONS_WIDTH, 16.0);
+                (d, Button::new(rect, Some(String::from(d.name())), Some(String::from(d.description()))))
+            }).collect(),
+            button_difficulty_back: Button::new(Rect::new(BUTTONS_BEGIN.x, BUTTONS_BEGIN.y + BUTTONS_GAP * 4.0, BUTTONS_WIDTH, 16.0), Some(String::from("Back")), None),
         }
     }
 
@@ -64,7 +75,7 @@ impl Menu {
         let dir = match fs::read_dir(".") {
             Ok(d) => d,
             Err(e) => {
-                toast_manager.add_toast(format!("{e}"), ToastKind::Warning);
+                toast_manager.add_toast(format!("{e}"), ToastKind::Error);
                 return vec![]
             }
         };
@@ -104,7 +115,24 @@ impl Menu {
             .saturating_sub(1)
     }
 
-    fn load_pack_file(&mut self, resources: &Resources) -> Option<LevelPackData> {
+    fn update_difficulty_select(&mut self, ui: &mut Ui, next_state: &mut Option<Box<dyn GameState>>) {
+        self.button_difficulty_back.update(ui);
+        if is_key_pressed(KeyCode::Escape) || self.button_difficulty_back.released() {
+            self.difficulty_pack = None;
+            return;
+        }
+        for (difficulty, button) in &mut self.button_difficulties {
+            button.update(ui);
+            if button.released() {
+                if let Some(pack) = self.difficulty_pack.take() {
+                    *next_state = Some(Box::new(Game::new(pack, *difficulty)));
+                }
+                return;
+            }
+        }
+    }
+
+    fn load_pack_file(&mut self, toast_manager: &mut ToastManager) -> Option<LevelPackData> {
         let pack_name = match self.pack_list.get(self.pack) {
             Some(p) => p,
             None => return None,
@@ -112,21 +140,21 @@ impl Menu {
         let bytes = match fs::read(format!("{}.fox", pack_name)) {
             Ok(b) => b,
             Err(e) => {
-                self.toast_manager.add_couldnt_pack_open_file();
-                self.toast_manager.add_toast(format!("{e}"), ToastKind::Warning);
+                toast_manager.add_couldnt_pack_open_file();
+                toast_manager.add_toast(format!("{e}"), ToastKind::Error);
                 return None;
             }
         };
-        let pack_data = LevelPackData::from_bytes(pack_name.clone(), &bytes, resources);
+        let pack_data = LevelPackData::from_bytes(pack_name.clone(), &bytes);
         if pack_data.is_none() {
-            self.toast_manager.add_invalid_pack_toast();
+            toast_manager.add_invalid_pack_toast();
         }
         pack_data
     }
 }
 
 impl GameState for Menu {
-    fn update(&mut self, deltatime: f32, ui: &mut Ui, resources: &mut Resources, next_state: &mut Option<Box<dyn GameState>>) {
+    fn update(&mut self, deltatime: f32, ui: &mut Ui, resources: &mut Resources, toast_manager: &mut ToastManager, next_state: &mut Option<Box<dyn GameState>>) {
         self.logo_timer = (self.logo_timer + deltatime).rem_euclid(PI);
         
         if self.submenu.is_some() {
@@ -138,12 +166,15 @@ impl GameState for Menu {
             }
         }
 
-        self.toast_manager.update(deltatime);
+        if self.difficulty_pack.is_some() {
+            self.update_difficulty_select(ui, next_state);
+            return;
+        }
 
         self.button_pack_refresh.update(ui);
         if self.button_pack_refresh.released() {
             let prev_pack = self.pack_list.get(self.pack).cloned();
-            self.pack_list = Self::get_pack_list(&mut self.toast_manager);
+            self.pack_list = Self::get_pack_list(toast_manager);
             self.pack = Self::index_in_pack(prev_pack, &self.pack_list);
         }
 
@@ -164,29 +195,37 @@ impl GameState for Menu {
         self.button_play.update(ui);
         self.button_edit_cur.update(ui);
         self.button_help.update(ui);
+        self.button_achievements.update(ui);
         self.button_editor.update(ui);
         self.button_credits.update(ui);
+        self.button_settings.update(ui);
         self.button_exit.update(ui);
 
         if self.button_play.released() || self.button_edit_cur.released() {
-            let pack = self.load_pack_file(&resources);
+            let pack = self.load_pack_file(toast_manager);
             if let Some(pack) = pack {
-                self.toast_manager.add_toast(format!("{}", pack.name()), ToastKind::Info);
-                self.toast_manager.add_toast(format!("{}", pack.author()), ToastKind::Info);
+                toast_manager.add_toast(format!("{}", pack.name()), ToastKind::Info);
+                toast_manager.add_toast(format!("{}", pack.author()), ToastKind::Info);
 
                 if self.button_edit_cur.released() {
                     *next_state = Some(Box::new(Editor::new(Some(pack), resources)));
                 } else {
-                    *next_state = Some(Box::new(Game::new(pack)));
+                    self.difficulty_pack = Some(pack);
                 }
             }
         }
         if self.button_help.released() {
             self.submenu.set_submenu_state(SubmenuState::Help);
         }
+        if self.button_achievements.released() {
+            self.submenu.set_submenu_state(SubmenuState::Achievements);
+        }
         if self.button_credits.released() {
             self.submenu.set_submenu_state(SubmenuState::Credits);
         }
+        if self.button_settings.released() {
+            self.submenu.set_submenu_state(SubmenuState::Settings);
+        }
         if self.button_editor.released() {
             *next_state = Some(Box::new(Editor::new(None, resources)));
         }
@@ -203,6 +242,16 @@ impl GameState for Menu {
             return;
         }
         
+        if self.difficulty_pack.is_some() {
+            render_text("Choose a difficulty:", WHITE, vec2(VIEW_SIZE.x / 2.0, PACK_SELECTOR_BEGIN.y + 8.0), Vec2::ONE, Align::Mid, Font::Small, resources);
+            for (_, button) in &self.button_difficulties {
+                button.draw(resources);
+            }
+            self.button_difficulty_back.draw(resources);
+            fancy_cute_rainbow_text("Fox Game", vec2(VIEW_SIZE.x / 2.0, 38.0), self.logo_timer, resources);
+            return;
+        }
+
         render_text("Load pack:", WHITE, vec2(PACK_SELECTOR_BEGIN.x, PACK_SELECTOR_BEGIN.y + 2.0), Vec2::ONE, Align::End, Font::Small, resources);
 
         let pack_pos = if self.pack_list.len() == 0 { 0 } else { self.pack + 1};
@@ -220,13 +269,13 @@ impl GameState for Menu {
         // Kinda a hacky way to add an image label to a button, but it's only done ONCE in the program! So meh...
         resources.draw_rect(self.button_edit_cur.rect().point() + 2.0, Rect::new(416.0, 64.0, 12.0, 12.0), false, false, WHITE, resources.entity_atlas());
         self.button_help.draw(resources);
+        self.button_achievements.draw(resources);
         self.button_editor.draw(resources);
         self.button_credits.draw(resources);
+        self.button_settings.draw(resources);
         self.button_exit.draw(resources);
 
         fancy_cute_rainbow_text("Fox Game", vec2(VIEW_SIZE.x / 2.0, 38.0), self.logo_timer, resources);
-        
-        self.toast_manager.draw(resources);   
     }
 }
 
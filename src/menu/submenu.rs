@@ -1,26 +1,36 @@
-use macroquad::{color::WHITE, math::{vec2, Vec2}};
+use macroquad::{color::{GRAY, LIGHTGRAY, WHITE, YELLOW}, math::{vec2, Vec2}};
 
-use crate::{resources::Resources, text_renderer::{render_text, Align, Font}, ui::{button::Button, Ui}, util::rect, VIEW_SIZE};
+use crate::{game::achievements::{Achievement, Achievements}, resources::Resources, settings::{Setting, Settings}, text_renderer::{render_text, text_size, Align, Font}, ui::{button::Button, Ui}, util::rect, VIEW_SIZE};
 
 use super::fancy_cute_rainbow_text;
 
 #[derive(PartialEq, Eq)]
 pub enum SubmenuState {
-    None, Help, Credits,
+    None, Help, Credits, Achievements, Settings,
 }
 
 pub struct Submenu {
     state: SubmenuState,
     back: Button,
+    achievements: Achievements,
+    settings: Settings,
+    setting_buttons: Vec<(Setting, Button)>,
 }
 
 impl Default for Submenu {
     fn default() -> Self {
         let size = vec2(80.0, 16.0);
         let pos = vec2(VIEW_SIZE.x / 2.0, VIEW_SIZE.y - 20.0);
+        let settings = Settings::load();
         Self {
             state: SubmenuState::None,
             back: Button::new(rect(pos - size / 2.0, size), Some("Back".to_string()), None),
+            achievements: Achievements::default(),
+            setting_buttons: Setting::ALL.iter().enumerate().map(|(i, &setting)| {
+                let rect = rect(vec2((VIEW_SIZE.x - 180.0) / 2.0, 70.0 + i as f32 * 18.0), vec2(180.0, 16.0));
+                (setting, Button::new(rect, Some(setting.label(&settings)), Some(String::from(setting.tooltip()))))
+            }).collect(),
+            settings,
         }
     }
 }
@@ -30,10 +40,28 @@ impl Submenu {
         self.state != SubmenuState::None
     }
     pub fn set_submenu_state(&mut self, state: SubmenuState) {
+        // Load them each time so they're up to date after playing
+        if state == SubmenuState::Achievements {
+            self.achievements = Achievements::load();
+        }
         self.state = state;
     }
 
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
     pub fn update(&mut self, ui: &mut Ui) {
+        if self.state == SubmenuState::Settings {
+            for (setting, button) in &mut self.setting_buttons {
+                button.update(ui);
+                if button.released() {
+                    self.settings.cycle(*setting);
+                    self.settings.save();
+                    button.set_label(setting.label(&self.settings));
+                }
+            }
+        }
         if self.state != SubmenuState::None {
             self.back.update(ui);
             if self.back.released() {
@@ -42,9 +70,32 @@ impl Submenu {
         }
     }
 
+    fn draw_achievements(&self, resources: &Resources) {
+        let mut pos = vec2(24.0, 70.0);
+        for achievement in Achievement::ALL {
+            let unlocked = self.achievements.unlocked(achievement);
+            let (name_col, desc_col) = match unlocked {
+                true  => (YELLOW, WHITE),
+                false => (GRAY, LIGHTGRAY),
+            };
+            render_text(achievement.name(), name_col, pos, Vec2::ONE, Align::End, Font::Small, resources);
+            let status = match (unlocked, self.achievements.progress(achievement)) {
+                (true, _) => String::from("Unlocked!"),
+                (false, Some((done, total))) => format!("{done}/{total}"),
+                (false, None) => String::new(),
+            };
+            let status_x = VIEW_SIZE.x - 24.0 - text_size(&status, Vec2::ONE, Font::Small, resources).x;
+            render_text(&status, name_col, vec2(status_x, pos.y), Vec2::ONE, Align::End, Font::Small, resources);
+            render_text(&format!(" {}", achievement.description()), desc_col, pos + vec2(0.0, 10.0), Vec2::ONE, Align::End, Font::Small, resources);
+            pos.y += 23.0;
+        }
+    }
+
     pub fn draw(&self, logo_timer: f32, resources: &Resources) {
         let (title, lines): (&str, &[&str]) = match self.state {
             SubmenuState::None => return,
+            SubmenuState::Achievements => ("Achievements", &[]),
+            SubmenuState::Settings => ("Settings", &[]),
             SubmenuState::Credits => ("Credits", &[
                 //-----------------------------------//
                 "             = FOX GAME =            ",
@@ -72,6 +123,7 @@ impl Submenu {
                 "General:",
                 " - Avoid enemies and collect powerups",
                 " - Reach the flag to finish a level",
+                " - Practice: 'R' rewinds, 'C' saves",
                 " - HAVE FUN!",
             ]),
         };
@@ -82,6 +134,14 @@ impl Submenu {
             render_text(&line, WHITE, pos, Vec2::ONE, Align::End, Font::Small, resources);
             pos.y += 10.0;
         }
+        if self.state == SubmenuState::Achievements {
+            self.draw_achievements(resources);
+        }
+        if self.state == SubmenuState::Settings {
+            for (_, button) in &self.setting_buttons {
+                button.draw(resources);
+            }
+        }
 
         self.back.draw(resources);
     }
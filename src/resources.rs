@@ -2,7 +2,7 @@ use macroquad::{color::Color, math::{Rect, Vec2}, texture::{draw_texture_ex, Dra
 
 use crate::{game::level::tile::{Tile, TileData, TileDataManager}, text_renderer::FontDataManager};
 
-const TILES_TEXTURE:  &[u8] = include_bytes!("../res/tiles.png");
+pub const TILES_TEXTURE:  &[u8] = include_bytes!("../res/tiles.png");
 const PLAYER_TEXTURE: &[u8] = include_bytes!("../res/player.png");
 const ENTITY_TEXTURE: &[u8] = include_bytes!("../res/entity.png");
 
@@ -0,0 +1,53 @@
+// Saving what's on screen to the screenshots folder
+// Each screenshot is saved twice, once at the game's actual resolution and once scaled up so it isn't tiny
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use macroquad::texture::{Image, Texture2D};
+
+const DIRECTORY: &str = "screenshots";
+const SCALE: u32 = 4;
+
+// The time as 'YYYY-MM-DD_HH-MM-SS-mmm' (in UTC, i'm not dealing with timezones)
+// The milliseconds are there so taking two screenshots in the same second doesn't overwrite the first one
+fn timestamp() -> String {
+    let millis = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0);
+    let (secs, millis) = (millis / 1000, millis % 1000);
+    let (days, secs) = (secs / 86400, secs % 86400);
+
+    // Days since 1970 to a date, from http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{year}-{month:0>2}-{day:0>2}_{:0>2}-{:0>2}-{:0>2}-{millis:0>3}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+fn scale_image(image: &Image, scale: u32) -> Image {
+    let mut scaled = Image::gen_image_color(image.width * scale as u16, image.height * scale as u16, Default::default());
+    for y in 0..scaled.height() as u32 {
+        for x in 0..scaled.width() as u32 {
+            scaled.set_pixel(x, y, image.get_pixel(x / scale, y / scale));
+        }
+    }
+    scaled
+}
+
+// Returns the name of the screenshot (without the folder or extension)
+pub fn save_screenshot(render_target: &Texture2D) -> Result<String, String> {
+    std::fs::create_dir_all(DIRECTORY).map_err(|e| format!("{e}"))?;
+
+    // Render targets are stored upside down, but export_png flips them back itself
+    let image = render_target.get_texture_data();
+
+    let name = format!("screenshot_{}", timestamp());
+    image.export_png(&format!("{DIRECTORY}/{name}.png"));
+    scale_image(&image, SCALE).export_png(&format!("{DIRECTORY}/{name}_x{SCALE}.png"));
+    Ok(name)
+}
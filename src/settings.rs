@@ -0,0 +1,119 @@
+// Settings that stick around between sessions, changed in the settings submenu
+// Each setting is a button that cycles through its values when it's clicked
+
+// Not a .fox file so it doesn't show up in the menu's pack list
+const FILE: &str = "settings.foxsave";
+const MAX_RESTART_LIFE_COST: u8 = 3;
+// Percentages, the game can only be slowed down
+const GAME_SPEEDS: [u8; 4] = [100, 75, 60, 50];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Setting {
+    RestartLifeCost,
+    Invincibility,
+    InfiniteLives,
+    GameSpeed,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 4] = [Self::RestartLifeCost, Self::Invincibility, Self::InfiniteLives, Self::GameSpeed];
+
+    pub fn label(self, settings: &Settings) -> String {
+        match self {
+            Self::RestartLifeCost => match settings.restart_life_cost {
+                0 => String::from("Restarting: free"),
+                1 => String::from("Restarting: 1 life"),
+                n => format!("Restarting: {n} lives"),
+            },
+            Self::Invincibility => format!("Invincibility: {}", on_off(settings.invincible)),
+            Self::InfiniteLives => format!("Infinite lives: {}", on_off(settings.infinite_lives)),
+            Self::GameSpeed     => format!("Game speed: {}%", settings.game_speed),
+        }
+    }
+
+    pub fn tooltip(self) -> &'static str {
+        match self {
+            Self::RestartLifeCost => "Lives lost when restarting a level",
+            Self::Invincibility   => "Assist: only pits can hurt you",
+            Self::InfiniteLives   => "Assist: lives never run out",
+            Self::GameSpeed       => "Assist: slows the game down",
+        }
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    match value {
+        true  => "On",
+        false => "Off",
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Settings {
+    restart_life_cost: u8,
+    // Assists
+    invincible: bool,
+    infinite_lives: bool,
+    game_speed: u8,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            restart_life_cost: 1,
+            invincible: false,
+            infinite_lives: false,
+            game_speed: 100,
+        }
+    }
+}
+
+impl Settings {
+    // If the file doesn't exist (or is broken), just use the defaults
+    pub fn load() -> Self {
+        let bytes = std::fs::read(FILE).unwrap_or_default();
+        match bytes.as_slice() {
+            &[restart_life_cost, invincible, infinite_lives, game_speed, ..] => Self {
+                restart_life_cost: restart_life_cost.min(MAX_RESTART_LIFE_COST),
+                invincible: invincible != 0,
+                infinite_lives: infinite_lives != 0,
+                game_speed: if GAME_SPEEDS.contains(&game_speed) { game_speed } else { 100 },
+            },
+            _ => Self::default(),
+        }
+    }
+
+    pub fn save(&self) {
+        let _ = std::fs::write(FILE, [self.restart_life_cost, self.invincible as u8, self.infinite_lives as u8, self.game_speed]);
+    }
+
+    pub fn restart_life_cost(&self) -> usize {
+        self.restart_life_cost as usize
+    }
+    pub fn invincible(&self) -> bool {
+        self.invincible
+    }
+    pub fn infinite_lives(&self) -> bool {
+        self.infinite_lives
+    }
+    pub fn game_speed(&self) -> f32 {
+        self.game_speed as f32 / 100.0
+    }
+    // Runs with any of these on don't count towards the NoGameOver achievement and have a note on the final stats
+    pub fn assists_enabled(&self) -> bool {
+        self.invincible || self.infinite_lives || self.game_speed != 100
+    }
+
+    // Moves a setting on to its next value, wrapping around at the end
+    pub fn cycle(&mut self, setting: Setting) {
+        match setting {
+            Setting::RestartLifeCost => self.restart_life_cost = (self.restart_life_cost + 1) % (MAX_RESTART_LIFE_COST + 1),
+            Setting::Invincibility   => self.invincible = !self.invincible,
+            Setting::InfiniteLives   => self.infinite_lives = !self.infinite_lives,
+            Setting::GameSpeed => {
+                let i = GAME_SPEEDS.iter().position(|&s| s == self.game_speed).unwrap_or_default();
+                self.game_speed = GAME_SPEEDS[(i + 1) % GAME_SPEEDS.len()];
+            }
+        }
+    }
+}
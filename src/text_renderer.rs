@@ -2,6 +2,14 @@ use macroquad::{color::Color, math::{vec2, Rect, Vec2}, texture::{draw_texture_e
 
 use crate::resources::Resources;
 
+const SMALL_ATLAS_CHARS: &str = " 0123456789:;abcdefghijklmnopqrstuvwxyz()[]<>!?.,\"'|\\/+-=*_'@£&🮤🮥🮧🮦↞↠▪🔄";
+
+// The same as FontData::typable_char for the small font, but without needing the font loaded (which needs the gpu)
+// Level packs are saved with this so they can be read without the game running
+pub fn typable_char(c: char) -> bool {
+    SMALL_ATLAS_CHARS.contains(c.to_ascii_lowercase()) && c.is_ascii()
+}
+
 pub struct FontData {
     char_width:   f32,
     char_height:  f32,
@@ -46,7 +54,7 @@ impl Default for FontDataManager {
                 char_height: 9.0,
                 char_spacing: -1.0,
                 atlas_width: 13,
-                atlas_chars: String::from(" 0123456789:;abcdefghijklmnopqrstuvwxyz()[]<>!?.,\"'|\\/+-=*_'@£&🮤🮥🮧🮦↞↠▪🔄"),
+                atlas_chars: String::from(SMALL_ATLAS_CHARS),
                 atlas: Texture2D::from_file_with_format(include_bytes!("../res/font_small.png"), None),
             },
         }
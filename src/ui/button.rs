@@ -28,6 +28,9 @@ impl Button {
     pub fn rect(&self) -> Rect {
         self.rect
     }
+    pub fn tooltip(&self) -> Option<&String> {
+        self.tooltip.as_ref()
+    }
     pub fn state(&self) -> ButtonState {
         self.state
     }
@@ -15,7 +15,7 @@ pub const TEXT_INPUT_RECT: Rect = Rect { x: 0.0, y: 0.0, w: 8.0 * MAX_USER_STRIN
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum TextInputKind {
-    All, FileName
+    All, FileName, Number
 }
 
 pub struct TextInput {
@@ -76,6 +76,7 @@ impl TextInput {
 
             if (self.kind == TextInputKind::All && resources.font_data_manager().font_data(Font::Small).typable_char(c))
             || (self.kind == TextInputKind::FileName && (c.is_ascii_alphanumeric() ||  c == '_' || c == '-'))
+            || (self.kind == TextInputKind::Number && c.is_ascii_digit())
             {
                 self.backspace_timer = None;
                 if text.len() < MAX_USER_STRING_LEN {
@@ -1,11 +1,31 @@
-use macroquad::{color::{Color, BLUE, RED}, math::{vec2, Rect, Vec2}};
+use macroquad::{color::{Color, BLUE, DARKGREEN, ORANGE, RED}, math::{vec2, Rect, Vec2}};
 
 use crate::{resources::Resources, text_renderer::{render_text, text_size, Align, Font}, util::draw_rect, VIEW_SIZE};
 
-use super::super::editor::editor_level::{MAX_CHECKPOINTS, MAX_DOORS, MAX_ENTITIES, MAX_SIGNS};
+use super::super::editor::{editor_level::{MAX_CHECKPOINTS, MAX_DOORS, MAX_ENTITIES, MAX_SIGNS}, editor_level_pack::MAX_LEVELS};
 
+// From least to most serious
 pub enum ToastKind {
-    Warning, Info,
+    Info, Success, Warning, Error,
+}
+
+impl ToastKind {
+    fn color(&self) -> Color {
+        match self {
+            ToastKind::Info    => BLUE,
+            ToastKind::Success => DARKGREEN,
+            ToastKind::Warning => ORANGE,
+            ToastKind::Error   => RED,
+        }
+    }
+
+    // Errors stick around longer so they aren't missed
+    fn duration(&self) -> f32 {
+        match self {
+            ToastKind::Error => 5.0,
+            _ => 3.0,
+        }
+    }
 }
 
 struct Toast {
@@ -14,6 +34,7 @@ struct Toast {
     timer: f32,
 }
 
+// There's only one of these, owned by the main loop and given to whichever game state is running
 #[derive(Default)]
 pub struct ToastManager {
     toasts: Vec<Toast>,
@@ -21,14 +42,14 @@ pub struct ToastManager {
 
 impl ToastManager {
     pub fn add_toast(&mut self, text: String, kind: ToastKind) {
-        self.toasts.push(Toast { text, kind, timer: 3.0 })
+        self.toasts.push(Toast { timer: kind.duration(), text, kind })
     }
 
     pub fn add_couldnt_pack_open_file(&mut self) {
-        self.add_toast(format!("Couldn't open pack file! (refresh?)"), ToastKind::Warning);
+        self.add_toast(format!("Couldn't open pack file! (refresh?)"), ToastKind::Error);
     }
     pub fn add_invalid_pack_toast(&mut self) {
-        self.add_toast(format!("Invalid level pack file!"), ToastKind::Warning);
+        self.add_toast(format!("Invalid level pack file!"), ToastKind::Error);
     }
 
     pub fn add_sign_limit_toast(&mut self) {
@@ -43,6 +64,9 @@ impl ToastManager {
     pub fn add_entitiy_limit_toast(&mut self) {
         self.add_toast(format!("Too many entities! (max {})", MAX_ENTITIES), ToastKind::Warning);
     }
+    pub fn add_level_limit_toast(&mut self) {
+        self.add_toast(format!("Too many levels! (max {})", MAX_LEVELS), ToastKind::Warning);
+    }
 
     pub fn update(&mut self, deltatime: f32) {
         for i in (0..self.toasts.len()).rev() {
@@ -60,10 +84,7 @@ impl ToastManager {
             let size = text_size(&t.text, Vec2::ONE, Font::Small, resources) + 4.0;
             let rect = Rect::new(pos.x - size.x / 2.0, pos.y - size.y / 2.0, size.x, size.y - 1.0);
 
-            let mut color = match t.kind {
-                ToastKind::Info => BLUE,
-                ToastKind::Warning => RED,
-            };
+            let mut color = t.kind.color();
             color.a = (t.timer - 0.0).clamp(0.0, 1.0);
 
             draw_rect(rect, color);